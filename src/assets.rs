@@ -0,0 +1,101 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{settings::MusicTrack, JUMP_SOUNDS};
+
+pub const FONT_PATH: &str = "fonts/Comic Sans MS.ttf";
+
+/// Assets shared by the whole game, loaded once at startup.
+#[derive(Resource)]
+pub struct GameAssets {
+    pub font: Handle<Font>,
+    /// In the order of `JUMP_SOUNDS`. Picked at random and played with a slightly
+    /// random pitch, so the jumps don't all sound the same.
+    pub jump_sounds: Vec<Handle<AudioSource>>,
+    /// Tracks in the order of `MusicTrack::ALL`.
+    pub music_tracks: Vec<Handle<AudioSource>>,
+}
+
+impl GameAssets {
+    pub fn music(&self, track: MusicTrack) -> Handle<AudioSource> {
+        let index = MusicTrack::ALL
+            .iter()
+            .position(|&other_track| other_track == track)
+            .unwrap_or(0);
+
+        self.music_tracks[index].clone()
+    }
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        GameAssets {
+            font: asset_server.load(FONT_PATH),
+            jump_sounds: JUMP_SOUNDS
+                .iter()
+                .map(|path| asset_server.load(*path))
+                .collect(),
+            music_tracks: MusicTrack::ALL
+                .iter()
+                .map(|track| asset_server.load(track.audio_path()))
+                .collect(),
+        }
+    }
+}
+
+/// Path of a file in the asset folder, which is found the same way as the `AssetServer` does:
+/// in the crate folder when run by cargo, next to the executable otherwise.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn asset_path(path: &str) -> std::path::PathBuf {
+    let base_path = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe_path| exe_path.parent().map(|parent| parent.to_path_buf()))
+        })
+        .unwrap_or_default();
+
+    base_path.join("assets").join(path)
+}
+
+pub struct GameAssetsPlugin;
+
+impl Plugin for GameAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameAssets>()
+            .add_systems(Update, fall_back_to_default_font);
+    }
+}
+
+// Switches all texts to the Bevy default font, if the custom font failed to load,
+// so the score stays visible.
+fn fall_back_to_default_font(
+    mut text_query: Query<&mut Text>,
+    mut game_assets: ResMut<GameAssets>,
+    asset_server: Res<AssetServer>,
+) {
+    let default_font = Handle::<Font>::default();
+
+    if game_assets.font == default_font
+        || asset_server.get_load_state(&game_assets.font) != LoadState::Failed
+    {
+        return;
+    }
+
+    warn!(
+        "Failed to load the font {}, using the default font instead",
+        FONT_PATH
+    );
+
+    let failed_font = std::mem::replace(&mut game_assets.font, default_font.clone());
+
+    for mut text in text_query.iter_mut() {
+        for section in text.sections.iter_mut() {
+            if section.style.font == failed_font {
+                section.style.font = default_font.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    COMBO_LANDING_TIME, DECOY_CHANCE, DECOY_MIN_SCORE, GRAVITY_STRENGTH, HIT_STOP_DURATION,
+    NEAR_MISS_MARGIN, OBSTACLES_MAX_NUM, OBSTACLE_MOVEMENT_SPEED, OBSTACLE_REVERSE_CHANCE,
+    PLANET_ROTATION_SPEED, PLANET_SHRINK_SPEED, PLAYER_DASH_ANGLE, PLAYER_DASH_COOLDOWN,
+    PLAYER_FALL_ACCELERATION, PLAYER_JUMP_CUT, PLAYER_JUMP_STRENGTH, PLAYER_MAX_RISE_VELOCITY,
+    PROJECTILE_LIFETIME, PROJECTILE_SPEED, SHOOTER_CHANCE, SHOOTER_FIRE_INTERVAL,
+    SHOOTER_MIN_SCORE,
+};
+
+/// Gameplay tuning in the asset folder, read at startup. Values missing from the file
+/// keep the built-in constant.
+pub const GAME_BALANCE_PATH: &str = "balance.ron";
+
+/// Tunable gameplay values, see the constants of the same name for what they do.
+/// Replays record a hash of them and refuse to play back with another balance.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameBalance {
+    pub player_jump_strength: f32,
+    pub player_jump_cut: f32,
+    pub gravity_strength: f32,
+    pub player_fall_acceleration: f32,
+    pub player_max_rise_velocity: f32,
+    /// Radians around the planet.
+    pub player_dash_angle: f32,
+    pub player_dash_cooldown: f32,
+    pub planet_rotation_speed: f32,
+    pub planet_shrink_speed: f32,
+    pub obstacle_movement_speed: f32,
+    pub obstacles_max_num: usize,
+    pub obstacle_reverse_chance: f64,
+    pub shooter_min_score: usize,
+    pub shooter_chance: f64,
+    pub shooter_fire_interval: f32,
+    pub decoy_min_score: usize,
+    pub decoy_chance: f64,
+    pub projectile_speed: f32,
+    pub projectile_lifetime: f32,
+    pub near_miss_margin: f32,
+    pub combo_landing_time: f32,
+    pub hit_stop_duration: f32,
+}
+
+impl Default for GameBalance {
+    fn default() -> Self {
+        GameBalance {
+            player_jump_strength: PLAYER_JUMP_STRENGTH,
+            player_jump_cut: PLAYER_JUMP_CUT,
+            gravity_strength: GRAVITY_STRENGTH,
+            player_fall_acceleration: PLAYER_FALL_ACCELERATION,
+            player_max_rise_velocity: PLAYER_MAX_RISE_VELOCITY,
+            player_dash_angle: PLAYER_DASH_ANGLE,
+            player_dash_cooldown: PLAYER_DASH_COOLDOWN,
+            planet_rotation_speed: PLANET_ROTATION_SPEED,
+            planet_shrink_speed: PLANET_SHRINK_SPEED,
+            obstacle_movement_speed: OBSTACLE_MOVEMENT_SPEED,
+            obstacles_max_num: OBSTACLES_MAX_NUM,
+            obstacle_reverse_chance: OBSTACLE_REVERSE_CHANCE,
+            shooter_min_score: SHOOTER_MIN_SCORE,
+            shooter_chance: SHOOTER_CHANCE,
+            shooter_fire_interval: SHOOTER_FIRE_INTERVAL,
+            decoy_min_score: DECOY_MIN_SCORE,
+            decoy_chance: DECOY_CHANCE,
+            projectile_speed: PROJECTILE_SPEED,
+            projectile_lifetime: PROJECTILE_LIFETIME,
+            near_miss_margin: NEAR_MISS_MARGIN,
+            combo_landing_time: COMBO_LANDING_TIME,
+            hit_stop_duration: HIT_STOP_DURATION,
+        }
+    }
+}
+
+impl GameBalance {
+    /// Describes the first value, that would break the game, if any.
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("player_jump_strength", self.player_jump_strength),
+            ("player_jump_cut", self.player_jump_cut),
+            ("gravity_strength", self.gravity_strength),
+            ("player_fall_acceleration", self.player_fall_acceleration),
+            ("player_max_rise_velocity", self.player_max_rise_velocity),
+            ("player_dash_angle", self.player_dash_angle),
+            ("player_dash_cooldown", self.player_dash_cooldown),
+            ("planet_rotation_speed", self.planet_rotation_speed),
+            ("planet_shrink_speed", self.planet_shrink_speed),
+            ("obstacle_movement_speed", self.obstacle_movement_speed),
+            ("shooter_fire_interval", self.shooter_fire_interval),
+            ("projectile_speed", self.projectile_speed),
+            ("projectile_lifetime", self.projectile_lifetime),
+            ("near_miss_margin", self.near_miss_margin),
+            ("combo_landing_time", self.combo_landing_time),
+            ("hit_stop_duration", self.hit_stop_duration),
+        ] {
+            if !value.is_finite() {
+                return Err(format!("{} has to be a finite number", name));
+            }
+        }
+
+        // Timers panic on negative durations.
+        for (name, value) in [
+            ("player_jump_strength", self.player_jump_strength),
+            ("player_max_rise_velocity", self.player_max_rise_velocity),
+            ("player_dash_cooldown", self.player_dash_cooldown),
+            ("planet_rotation_speed", self.planet_rotation_speed),
+            ("planet_shrink_speed", self.planet_shrink_speed),
+            ("obstacle_movement_speed", self.obstacle_movement_speed),
+            ("projectile_speed", self.projectile_speed),
+            ("projectile_lifetime", self.projectile_lifetime),
+            ("near_miss_margin", self.near_miss_margin),
+            ("combo_landing_time", self.combo_landing_time),
+            ("hit_stop_duration", self.hit_stop_duration),
+        ] {
+            if value < 0. {
+                return Err(format!("{} can't be negative", name));
+            }
+        }
+
+        // A repeating timer of zero length would fire endlessly.
+        if self.shooter_fire_interval <= 0. {
+            return Err("shooter_fire_interval has to be above 0".to_string());
+        }
+
+        if self.gravity_strength >= 0. {
+            return Err("gravity_strength has to pull down, below 0".to_string());
+        }
+
+        if self.obstacles_max_num == 0 {
+            return Err("obstacles_max_num has to be at least 1".to_string());
+        }
+
+        if !(0. ..=1.).contains(&self.player_jump_cut) {
+            return Err("player_jump_cut has to be between 0 and 1".to_string());
+        }
+
+        for (name, chance) in [
+            ("obstacle_reverse_chance", self.obstacle_reverse_chance),
+            ("shooter_chance", self.shooter_chance),
+            ("decoy_chance", self.decoy_chance),
+        ] {
+            if !(0. ..=1.).contains(&chance) {
+                return Err(format!("{} has to be between 0 and 1", name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_game_balance());
+    }
+}
+
+/// Reads `GAME_BALANCE_PATH`, falling back to the built-in constants.
+pub fn load_game_balance() -> GameBalance {
+    let Some(data) = read_game_balance() else {
+        info!("No {}, using the built-in balance", GAME_BALANCE_PATH);
+        return GameBalance::default();
+    };
+
+    let game_balance = match ron::from_str::<GameBalance>(&data) {
+        Ok(game_balance) => game_balance,
+        Err(error) => {
+            warn!(
+                "Failed to parse {}, using the built-in balance: {}",
+                GAME_BALANCE_PATH, error
+            );
+            return GameBalance::default();
+        }
+    };
+
+    if let Err(error) = game_balance.validate() {
+        warn!(
+            "Invalid {}, using the built-in balance: {}",
+            GAME_BALANCE_PATH, error
+        );
+        return GameBalance::default();
+    }
+
+    game_balance
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_game_balance() -> Option<String> {
+    std::fs::read_to_string(crate::assets::asset_path(GAME_BALANCE_PATH)).ok()
+}
+
+// The web build has no file system, the balance is baked in.
+#[cfg(target_arch = "wasm32")]
+fn read_game_balance() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_balance_is_valid() {
+        assert_eq!(GameBalance::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_negative_durations_and_speeds() {
+        for set_value in [
+            |balance: &mut GameBalance| balance.player_dash_cooldown = -1.,
+            |balance: &mut GameBalance| balance.hit_stop_duration = -0.1,
+            |balance: &mut GameBalance| balance.shooter_fire_interval = 0.,
+            |balance: &mut GameBalance| balance.projectile_lifetime = -4.,
+            |balance: &mut GameBalance| balance.projectile_speed = -250.,
+            |balance: &mut GameBalance| balance.obstacle_movement_speed = -2.,
+        ] {
+            let mut balance = GameBalance::default();
+            set_value(&mut balance);
+
+            assert!(balance.validate().is_err(), "{:?}", balance);
+        }
+    }
+
+    #[test]
+    fn rejects_non_finite_values() {
+        let balance = GameBalance {
+            player_dash_cooldown: f32::NAN,
+            ..default()
+        };
+        assert!(balance.validate().is_err());
+
+        let balance = GameBalance {
+            projectile_lifetime: f32::INFINITY,
+            ..default()
+        };
+        assert!(balance.validate().is_err());
+    }
+}
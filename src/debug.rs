@@ -0,0 +1,402 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameAssets,
+    balance::GameBalance,
+    game_time::TimeScale,
+    jump_peak_height,
+    settings::{GameSettings, GRAVITY_SCALE_RANGE, GRAVITY_SCALE_STEP},
+    short_jump_peak_height, AppState, DeathCause, LoadingState, Obstacle, Planet,
+    PlanetActivatedEvent, PlanetClearedEvent, PlanetSpawnEvent, Player, PlayerHitEvent,
+    PlayerLandedEvent, RunConfig,
+};
+
+pub const DEBUG_PANEL_KEY: KeyCode = KeyCode::F3;
+/// Change the gravity scale, while the debug panel is open.
+pub const GRAVITY_DOWN_KEY: KeyCode = KeyCode::BracketLeft;
+pub const GRAVITY_UP_KEY: KeyCode = KeyCode::BracketRight;
+/// Picks the score of the next runs from `STARTING_SCORES`, while the debug panel is open.
+pub const STARTING_SCORE_KEY: KeyCode = KeyCode::F6;
+pub const STARTING_SCORES: [usize; 5] = [0, 6, 12, 25, 50];
+/// Slows the gameplay down to the next of `TIME_SCALES`, while the debug panel is open.
+pub const TIME_SCALE_KEY: KeyCode = KeyCode::F7;
+pub const TIME_SCALES: [f32; 3] = [1., 0.5, 0.25];
+/// Ends the run right away, to get to the game over screen quickly.
+/// A function key like the others, so it can't be one of the gameplay keys.
+pub const FORCE_GAME_OVER_KEY: KeyCode = KeyCode::F8;
+/// Entries kept in the `EventLog`, older ones scroll away.
+pub const EVENT_LOG_LENGTH: usize = 15;
+
+pub const DEBUG_PANEL_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.top = Val::Px(12.);
+    style.left = Val::Px(12.);
+    style
+};
+
+pub const EVENT_LOG_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.bottom = Val::Px(12.);
+    style.right = Val::Px(12.);
+    style
+};
+
+pub const DEBUG_PANEL_BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.6);
+
+#[derive(Component)]
+pub struct DebugPanel;
+
+#[derive(Component)]
+pub struct EventLogText;
+
+/// The last `EVENT_LOG_LENGTH` game events and state changes, oldest first.
+#[derive(Resource, Default)]
+pub struct EventLog {
+    entries: VecDeque<String>,
+}
+
+impl EventLog {
+    fn push(&mut self, time: &Time, entry: String) {
+        if self.entries.len() == EVENT_LOG_LENGTH {
+            self.entries.pop_front();
+        }
+
+        self.entries
+            .push_back(format!("[{:.2}] {}", time.elapsed_seconds(), entry));
+    }
+}
+
+/// Live game values and a log of recent events for development, toggled with F3.
+/// Only in debug builds.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        app.init_resource::<EventLog>()
+            .add_systems(Startup, (spawn_debug_panel, spawn_event_log))
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_panel,
+                    adjust_gravity_scale,
+                    cycle_starting_score,
+                    cycle_time_scale,
+                    update_debug_panel,
+                    log_events,
+                    update_event_log.after(log_events),
+                    warn_parented_obstacles,
+                    force_game_over.run_if(in_state(AppState::Playing)),
+                ),
+            );
+    }
+}
+
+fn spawn_debug_panel(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            style: DEBUG_PANEL_STYLE,
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            ),
+            background_color: DEBUG_PANEL_BACKGROUND_COLOR.into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        DebugPanel,
+    ));
+}
+
+fn spawn_event_log(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            style: EVENT_LOG_STYLE,
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                },
+            ),
+            background_color: DEBUG_PANEL_BACKGROUND_COLOR.into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        EventLogText,
+    ));
+}
+
+fn toggle_debug_panel(
+    mut debug_panel_query: Query<&mut Visibility, Or<(With<DebugPanel>, With<EventLogText>)>>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    if !keyboard.just_pressed(DEBUG_PANEL_KEY) {
+        return;
+    }
+
+    for mut visibility in debug_panel_query.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn log_events(
+    mut event_log: ResMut<EventLog>,
+    mut planet_spawn_event_reader: EventReader<PlanetSpawnEvent>,
+    mut planet_activated_event_reader: EventReader<PlanetActivatedEvent>,
+    mut planet_cleared_event_reader: EventReader<PlanetClearedEvent>,
+    mut player_landed_event_reader: EventReader<PlayerLandedEvent>,
+    mut player_hit_event_reader: EventReader<PlayerHitEvent>,
+    app_state: Res<State<AppState>>,
+    loading_state: Res<State<LoadingState>>,
+    time: Res<Time>,
+) {
+    if app_state.is_changed() {
+        event_log.push(&time, format!("AppState -> {:?}", app_state.get()));
+    }
+
+    if loading_state.is_changed() {
+        let entry = match loading_state.get() {
+            LoadingState::None => "Loading complete".to_string(),
+            loading_state => format!("LoadingState -> {:?}", loading_state),
+        };
+        event_log.push(&time, entry);
+    }
+
+    for planet_spawn_event in planet_spawn_event_reader.iter() {
+        event_log.push(
+            &time,
+            format!(
+                "Planet spawned: {:?}",
+                planet_spawn_event.planet_variant_to_spawn
+            ),
+        );
+    }
+
+    for planet_activated_event in planet_activated_event_reader.iter() {
+        event_log.push(
+            &time,
+            format!("Planet activated: {:?}", planet_activated_event.variant),
+        );
+    }
+
+    for planet_cleared_event in planet_cleared_event_reader.iter() {
+        event_log.push(
+            &time,
+            format!("Planet cleared: {:?}", planet_cleared_event.variant),
+        );
+    }
+
+    for player_landed_event in player_landed_event_reader.iter() {
+        event_log.push(
+            &time,
+            format!(
+                "Landed at ({:.0}, {:.0})",
+                player_landed_event.position.x, player_landed_event.position.y
+            ),
+        );
+    }
+
+    for player_hit_event in player_hit_event_reader.iter() {
+        event_log.push(&time, format!("Player hit: {:?}", player_hit_event.cause));
+    }
+}
+
+fn update_event_log(
+    mut event_log_query: Query<(&mut Text, &Visibility), With<EventLogText>>,
+    event_log: Res<EventLog>,
+) {
+    let Ok((mut text, visibility)) = event_log_query.get_single_mut() else {
+        return;
+    };
+
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    text.sections[0].value = event_log
+        .entries
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+}
+
+// Works like a slider for the gravity, while the panel is open.
+fn adjust_gravity_scale(
+    mut settings: ResMut<GameSettings>,
+    debug_panel_query: Query<&Visibility, With<DebugPanel>>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    if debug_panel_query
+        .get_single()
+        .map_or(true, |visibility| *visibility == Visibility::Hidden)
+    {
+        return;
+    }
+
+    let step = if keyboard.just_pressed(GRAVITY_UP_KEY) {
+        GRAVITY_SCALE_STEP
+    } else if keyboard.just_pressed(GRAVITY_DOWN_KEY) {
+        -GRAVITY_SCALE_STEP
+    } else {
+        return;
+    };
+
+    settings.gravity_scale =
+        (settings.gravity_scale + step).clamp(GRAVITY_SCALE_RANGE.0, GRAVITY_SCALE_RANGE.1);
+}
+
+fn force_game_over(
+    mut commands: Commands,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    if keyboard.just_pressed(FORCE_GAME_OVER_KEY) {
+        commands.insert_resource(DeathCause::Forced);
+        next_app_state.set(AppState::GameOver);
+    }
+}
+
+fn cycle_starting_score(
+    mut run_config: ResMut<RunConfig>,
+    debug_panel_query: Query<&Visibility, With<DebugPanel>>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    if !keyboard.just_pressed(STARTING_SCORE_KEY)
+        || debug_panel_query
+            .get_single()
+            .map_or(true, |visibility| *visibility == Visibility::Hidden)
+    {
+        return;
+    }
+
+    run_config.starting_score = STARTING_SCORES
+        .iter()
+        .copied()
+        .find(|&score| score > run_config.starting_score)
+        .unwrap_or(STARTING_SCORES[0]);
+}
+
+// Slow motion for looking at collisions and animations. Slowed runs aren't recorded,
+// see `record_frame`.
+fn cycle_time_scale(
+    mut time_scale: ResMut<TimeScale>,
+    debug_panel_query: Query<&Visibility, With<DebugPanel>>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    if !keyboard.just_pressed(TIME_SCALE_KEY)
+        || debug_panel_query
+            .get_single()
+            .map_or(true, |visibility| *visibility == Visibility::Hidden)
+    {
+        return;
+    }
+
+    time_scale.0 = TIME_SCALES
+        .iter()
+        .copied()
+        .find(|&scale| scale < time_scale.0)
+        .unwrap_or(TIME_SCALES[0]);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_debug_panel(
+    mut debug_panel_query: Query<(&mut Text, &Visibility), With<DebugPanel>>,
+    planet_query: Query<&Planet>,
+    obstacle_query: Query<(), With<Obstacle>>,
+    player_query: Query<&Player>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    time_scale: Res<TimeScale>,
+    app_state: Res<State<AppState>>,
+    loading_state: Res<State<LoadingState>>,
+) {
+    let Ok((mut text, visibility)) = debug_panel_query.get_single_mut() else {
+        return;
+    };
+
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let mut lines = vec![
+        format!("AppState: {:?}", app_state.get()),
+        format!("LoadingState: {:?}", loading_state.get()),
+        format!(
+            "Gravity scale: {:.1} ({:?}/{:?}), jump peak {:.0}-{:.0}px",
+            settings.gravity_scale,
+            GRAVITY_DOWN_KEY,
+            GRAVITY_UP_KEY,
+            short_jump_peak_height(
+                &game_balance,
+                settings.gravity_scale,
+                settings.jump_strength_scale
+            ),
+            jump_peak_height(
+                &game_balance,
+                settings.gravity_scale,
+                settings.jump_strength_scale
+            )
+        ),
+        format!(
+            "Starting score: {} ({:?}, next run)",
+            run_config.starting_score, STARTING_SCORE_KEY
+        ),
+        format!("Time scale: {} ({:?})", time_scale.0, TIME_SCALE_KEY),
+    ];
+
+    for planet_struct in planet_query.iter() {
+        lines.push(format!(
+            "Planet {:?}: radius {:.1}, is_playing {}, obstacles {}",
+            planet_struct.variant,
+            planet_struct.radius,
+            planet_struct.is_playing,
+            planet_struct.obstacles.len()
+        ));
+    }
+
+    lines.push(format!("Obstacles total: {}", obstacle_query.iter().len()));
+
+    if let Ok(player_struct) = player_query.get_single() {
+        lines.push(format!(
+            "Player: velocity {:.1}, grounded {}",
+            player_struct.velocity, player_struct.is_grounded
+        ));
+    }
+
+    text.sections[0].value = lines.join("\n");
+}
+
+// Obstacles have to stay top-level, see `despawn_obstacles`. A parent would despawn them
+// along with it, or take them along when the parent despawns.
+fn warn_parented_obstacles(
+    obstacle_query: Query<(Entity, &Parent), (With<Obstacle>, Added<Parent>)>,
+) {
+    for (obstacle_entity, parent) in obstacle_query.iter() {
+        warn!(
+            "Obstacle {:?} got parented to {:?}, it should stay top-level",
+            obstacle_entity,
+            parent.get()
+        );
+    }
+}
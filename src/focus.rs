@@ -0,0 +1,213 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{pause::PAUSE_KEY, replay::ReplayPlayback, settings::GameSettings};
+
+pub const FOCUS_BORDER_WIDTH: f32 = 4.;
+pub const FOCUS_BORDER_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+// How far the left stick has to be pushed to move the focus.
+pub const FOCUS_STICK_THRESHOLD: f32 = 0.5;
+
+/// Button picked with the gamepad or the arrow keys and Tab, activated with the South button
+/// (A on Xbox pads) or Enter.
+#[derive(Resource, Default)]
+pub struct MenuFocus {
+    pub button: Option<Entity>,
+}
+
+/// Lets the gamepad and the keyboard move between the visible buttons and press them,
+/// without a mouse. The gamepad also plays the runs, through the keys of `KeyBindings`.
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .add_systems(
+                PreUpdate,
+                press_keys_with_gamepad
+                    .after(InputSystem)
+                    .run_if(not(resource_exists::<ReplayPlayback>())),
+            )
+            .add_systems(
+                Update,
+                (move_focus, press_focused_button, highlight_focused_button).chain(),
+            );
+    }
+}
+
+// Holds the bound keys while their gamepad buttons are held, so the gameplay systems
+// and the replay recording read the gamepad like the keyboard.
+fn press_keys_with_gamepad(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    settings: Res<GameSettings>,
+) {
+    let key_bindings = &settings.key_bindings;
+
+    for gamepad in gamepads.iter() {
+        for (button_type, key) in [
+            (GamepadButtonType::South, key_bindings.jump),
+            (GamepadButtonType::LeftTrigger2, key_bindings.fast_fall),
+            (GamepadButtonType::East, key_bindings.dash),
+            (GamepadButtonType::RightTrigger2, key_bindings.dash),
+            (GamepadButtonType::Start, PAUSE_KEY),
+        ] {
+            let button = GamepadButton::new(gamepad, button_type);
+
+            // Only the changes, so the keyboard keeps working alongside.
+            if gamepad_buttons.just_pressed(button) {
+                keyboard_input.press(key);
+            } else if gamepad_buttons.just_released(button) {
+                keyboard_input.release(key);
+            }
+        }
+    }
+}
+
+fn is_focusable(node: &Node, computed_visibility: &ComputedVisibility) -> bool {
+    // Hidden panels have `Display::None`, which leaves them without a size.
+    computed_visibility.is_visible() && node.size() != Vec2::ZERO
+}
+
+// Steps through the buttons in reading order, top to bottom and left to right.
+fn move_focus(
+    mut menu_focus: ResMut<MenuFocus>,
+    button_query: Query<(Entity, &Node, &ComputedVisibility, &GlobalTransform), With<Button>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    keyboard: Res<Input<KeyCode>>,
+    mut is_stick_held: Local<bool>,
+) {
+    let mut step = 0;
+    let mut is_stick_pushed = false;
+
+    if keyboard.just_pressed(KeyCode::Tab) {
+        step = if keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            -1
+        } else {
+            1
+        };
+    } else if keyboard.any_just_pressed([KeyCode::Down, KeyCode::Right]) {
+        step = 1;
+    } else if keyboard.any_just_pressed([KeyCode::Up, KeyCode::Left]) {
+        step = -1;
+    }
+
+    for gamepad in gamepads.iter() {
+        let is_just_pressed =
+            |button_type| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+
+        if is_just_pressed(GamepadButtonType::DPadDown)
+            || is_just_pressed(GamepadButtonType::DPadRight)
+        {
+            step = 1;
+        } else if is_just_pressed(GamepadButtonType::DPadUp)
+            || is_just_pressed(GamepadButtonType::DPadLeft)
+        {
+            step = -1;
+        }
+
+        let stick_y = gamepad_axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or(0.);
+
+        if stick_y.abs() > FOCUS_STICK_THRESHOLD {
+            is_stick_pushed = true;
+
+            // Moves once per push, the stick has to come back to the center first.
+            if !*is_stick_held {
+                step = if stick_y < 0. { 1 } else { -1 };
+            }
+        }
+    }
+    *is_stick_held = is_stick_pushed;
+
+    let mut buttons: Vec<(Entity, Vec3)> = button_query
+        .iter()
+        .filter(|(_, node, computed_visibility, _)| is_focusable(node, computed_visibility))
+        .map(|(entity, _, _, global_transform)| (entity, global_transform.translation()))
+        .collect();
+
+    // Forget the button, once it's hidden or despawned.
+    if menu_focus.button.is_some_and(|focused_button| {
+        !buttons
+            .iter()
+            .any(|&(button_entity, _)| button_entity == focused_button)
+    }) {
+        menu_focus.button = None;
+    }
+
+    if step == 0 || buttons.is_empty() {
+        return;
+    }
+
+    // UI positions grow downward.
+    buttons.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let next_index = match menu_focus.button.and_then(|focused_button| {
+        buttons
+            .iter()
+            .position(|&(button_entity, _)| button_entity == focused_button)
+    }) {
+        Some(index) => (index as isize + step).rem_euclid(buttons.len() as isize) as usize,
+        None => 0,
+    };
+
+    menu_focus.button = Some(buttons[next_index].0);
+}
+
+// Presses the focused button for a frame, so the usual `Interaction` handlers react to it.
+fn press_focused_button(
+    mut interaction_query: Query<&mut Interaction, With<Button>>,
+    menu_focus: Res<MenuFocus>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    keyboard: Res<Input<KeyCode>>,
+    mut pressed_button: Local<Option<Entity>>,
+) {
+    if let Some(button_entity) = pressed_button.take() {
+        if let Ok(mut interaction) = interaction_query.get_mut(button_entity) {
+            if *interaction == Interaction::Pressed {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+
+    let Some(focused_button) = menu_focus.button else {
+        return;
+    };
+
+    let is_confirmed = keyboard.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter])
+        || gamepads.iter().any(|gamepad| {
+            gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+        });
+
+    if !is_confirmed {
+        return;
+    }
+
+    if let Ok(mut interaction) = interaction_query.get_mut(focused_button) {
+        *interaction = Interaction::Pressed;
+        *pressed_button = Some(focused_button);
+    }
+}
+
+fn highlight_focused_button(
+    mut button_query: Query<(Entity, &mut Style, &mut BorderColor), With<Button>>,
+    menu_focus: Res<MenuFocus>,
+) {
+    if !menu_focus.is_changed() {
+        return;
+    }
+
+    for (button_entity, mut style, mut border_color) in button_query.iter_mut() {
+        if menu_focus.button == Some(button_entity) {
+            style.border = UiRect::all(Val::Px(FOCUS_BORDER_WIDTH));
+            border_color.0 = FOCUS_BORDER_COLOR;
+        } else if style.border != UiRect::DEFAULT {
+            style.border = UiRect::DEFAULT;
+            border_color.0 = Color::NONE;
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{gameplay_delta, pause::PauseState, HitStop};
+
+/// Speed of the gameplay clock, 1 is real time. Slowed down with the debug `TIME_SCALE_KEY`.
+#[derive(Resource)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale(1.)
+    }
+}
+
+/// Frame time of the gameplay, read by the gameplay systems instead of `Time`.
+/// Zero while paused or during the hit-stop, otherwise capped at `MAX_GAMEPLAY_DELTA`
+/// and scaled by the `TimeScale`.
+#[derive(Resource, Default)]
+pub struct GameTime {
+    delta: Duration,
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+}
+
+pub struct GameTimePlugin;
+
+impl Plugin for GameTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .init_resource::<GameTime>()
+            .add_systems(PreUpdate, update_game_time);
+    }
+}
+
+fn update_game_time(
+    mut game_time: ResMut<GameTime>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    pause_state: Res<State<PauseState>>,
+    hit_stop: Option<Res<HitStop>>,
+) {
+    let is_frozen = *pause_state.get() == PauseState::Paused || hit_stop.is_some();
+
+    game_time.delta = game_delta(gameplay_delta(&time), is_frozen, time_scale.0);
+}
+
+/// Gameplay frame time out of the capped real one.
+pub fn game_delta(delta: Duration, is_frozen: bool, time_scale: f32) -> Duration {
+    if is_frozen {
+        return Duration::ZERO;
+    }
+
+    // Scaling by 1 could round off a nanosecond and throw recorded replays out of sync.
+    if time_scale == 1. {
+        return delta;
+    }
+
+    delta.mul_f32(time_scale.max(0.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Exact in binary, so the scaled deltas compare equal.
+    const FRAME: Duration = Duration::from_millis(250);
+
+    #[test]
+    fn frozen_delta_is_zero() {
+        assert_eq!(game_delta(FRAME, true, 1.), Duration::ZERO);
+    }
+
+    #[test]
+    fn delta_follows_the_time_scale() {
+        assert_eq!(game_delta(FRAME, false, 1.), FRAME);
+        assert_eq!(game_delta(FRAME, false, 0.5), Duration::from_millis(125));
+        assert_eq!(game_delta(FRAME, false, 2.), Duration::from_millis(500));
+        assert_eq!(game_delta(FRAME, false, -1.), Duration::ZERO);
+    }
+
+    #[test]
+    fn frozen_delta_ignores_the_time_scale() {
+        assert_eq!(game_delta(FRAME, true, 0.5), Duration::ZERO);
+        assert_eq!(game_delta(FRAME, true, 2.), Duration::ZERO);
+    }
+}
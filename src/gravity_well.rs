@@ -0,0 +1,125 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{warp::Transitioning, Planet};
+
+/// Size of the well relative to the planet sprite, it reaches this far out from the center.
+pub const GRAVITY_WELL_SCALE: f32 = 1.6;
+pub const GRAVITY_WELL_COLOR: Color = Color::rgb(0.55, 0.75, 1.);
+pub const GRAVITY_WELL_ACTIVE_ALPHA: f32 = 0.35;
+pub const GRAVITY_WELL_INACTIVE_ALPHA: f32 = 0.1;
+// Alpha per second, so the well fades in over about a second after the transition.
+pub const GRAVITY_WELL_FADE_SPEED: f32 = 0.4;
+pub const GRAVITY_WELL_TEXTURE_SIZE: u32 = 128;
+
+/// Radial gradient drawn behind the planet, shrinking with it.
+#[derive(Component)]
+pub struct GravityWell;
+
+/// Gradient shared by every well, generated at startup, there's no art for it.
+#[derive(Resource)]
+pub struct GravityWellTexture(Handle<Image>);
+
+/// Soft glow around each planet, showing the reach of its gravity. Purely cosmetic,
+/// it's a child of the planet without a collider.
+pub struct GravityWellPlugin;
+
+impl Plugin for GravityWellPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_gravity_well_texture)
+            .add_systems(Update, (spawn_gravity_wells, update_gravity_wells).chain());
+    }
+}
+
+fn create_gravity_well_texture(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = GRAVITY_WELL_TEXTURE_SIZE;
+    let center = (size as f32 - 1.) / 2.;
+
+    let data = (0..size * size)
+        .flat_map(|index| {
+            let offset = Vec2::new((index % size) as f32, (index / size) as f32) - center;
+            let alpha = gravity_well_alpha(offset.length() / center);
+            [255, 255, 255, (alpha * 255.) as u8]
+        })
+        .collect();
+
+    let image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    commands.insert_resource(GravityWellTexture(images.add(image)));
+}
+
+/// Opacity of the gradient at the distance from the center, 1 being the edge.
+/// Strongest at the planet surface, the inside is covered by the planet anyway.
+pub fn gravity_well_alpha(distance: f32) -> f32 {
+    let surface = 1. / GRAVITY_WELL_SCALE;
+    let falloff = ((1. - distance) / (1. - surface)).clamp(0., 1.);
+
+    falloff * falloff
+}
+
+fn spawn_gravity_wells(
+    mut commands: Commands,
+    planet_query: Query<Entity, Added<Planet>>,
+    gravity_well_texture: Res<GravityWellTexture>,
+) {
+    for planet_entity in planet_query.iter() {
+        commands.entity(planet_entity).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    texture: gravity_well_texture.0.clone(),
+                    sprite: Sprite {
+                        color: GRAVITY_WELL_COLOR.with_a(0.),
+                        ..default()
+                    },
+                    // Behind the planet, in front of the background.
+                    transform: Transform::from_xyz(0., 0., -1.),
+                    ..default()
+                },
+                GravityWell,
+            ));
+        });
+    }
+}
+
+// Follows the planet size, and fades out while the camera warps to the next planet.
+fn update_gravity_wells(
+    mut gravity_well_query: Query<(&Parent, &mut Sprite), With<GravityWell>>,
+    planet_query: Query<(&Sprite, &Planet), Without<GravityWell>>,
+    transitioning: Option<Res<Transitioning>>,
+    time: Res<Time>,
+) {
+    for (parent, mut well_sprite) in gravity_well_query.iter_mut() {
+        let Ok((planet_sprite, planet_struct)) = planet_query.get(parent.get()) else {
+            continue;
+        };
+
+        well_sprite.custom_size = planet_sprite
+            .custom_size
+            .map(|planet_size| planet_size * GRAVITY_WELL_SCALE);
+
+        let target_alpha = if transitioning.is_some() {
+            0.
+        } else if planet_struct.is_playing {
+            GRAVITY_WELL_ACTIVE_ALPHA
+        } else {
+            GRAVITY_WELL_INACTIVE_ALPHA
+        };
+
+        let alpha = well_sprite.color.a();
+        let step = GRAVITY_WELL_FADE_SPEED * time.delta_seconds();
+        well_sprite
+            .color
+            .set_a(alpha + (target_alpha - alpha).clamp(-step, step));
+    }
+}
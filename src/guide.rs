@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::{assets::GameAssets, settings::GameSettings, AppState, CurrentPlanet, Planet};
+
+/// Degrees between the ticks, counter-clockwise from the right like the authored angles.
+pub const ANGLE_GUIDE_STEP: usize = 30;
+pub const ANGLE_GUIDE_TICK_LENGTH: f32 = 16.;
+// Distance of the labels from the planet surface.
+pub const ANGLE_GUIDE_LABEL_OFFSET: f32 = 36.;
+pub const ANGLE_GUIDE_COLOR: Color = Color::rgba(1., 1., 1., 0.4);
+
+/// Degrees label of one tick.
+#[derive(Component)]
+pub struct AngleGuideLabel {
+    degrees: usize,
+}
+
+/// Tick marks with degrees around the current planet, to learn the obstacle angles.
+/// Shown with the angle guide setting, it doesn't change the gameplay.
+pub struct AngleGuidePlugin;
+
+impl Plugin for AngleGuidePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_angle_guide_labels)
+            .add_systems(Update, draw_angle_guide);
+    }
+}
+
+fn spawn_angle_guide_labels(mut commands: Commands, game_assets: Res<GameAssets>) {
+    for degrees in (0..360).step_by(ANGLE_GUIDE_STEP) {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{}°", degrees),
+                    TextStyle {
+                        font: game_assets.font.clone(),
+                        font_size: 16.0,
+                        color: ANGLE_GUIDE_COLOR,
+                    },
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            AngleGuideLabel { degrees },
+        ));
+    }
+}
+
+fn draw_angle_guide(
+    mut gizmos: Gizmos,
+    mut label_query: Query<(&mut Transform, &mut Visibility, &AngleGuideLabel)>,
+    planet_query: Query<&Transform, (With<Planet>, Without<AngleGuideLabel>)>,
+    current_planet: Res<CurrentPlanet>,
+    settings: Res<GameSettings>,
+    app_state: Res<State<AppState>>,
+) {
+    let planet_transform = current_planet
+        .entity
+        .and_then(|planet_entity| planet_query.get(planet_entity).ok())
+        .filter(|_| settings.show_angle_guide && *app_state.get() == AppState::Playing);
+
+    let Some(planet_transform) = planet_transform else {
+        for (_, mut visibility, _) in label_query.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    let center = planet_transform.translation.truncate();
+    let radius = current_planet.radius;
+
+    for (mut label_transform, mut visibility, label) in label_query.iter_mut() {
+        let direction = Vec2::from_angle((label.degrees as f32).to_radians());
+
+        gizmos.line_2d(
+            center + direction * radius,
+            center + direction * (radius + ANGLE_GUIDE_TICK_LENGTH),
+            ANGLE_GUIDE_COLOR,
+        );
+
+        // In front of the planet and the obstacles.
+        label_transform.translation = (center
+            + direction * (radius + ANGLE_GUIDE_TICK_LENGTH + ANGLE_GUIDE_LABEL_OFFSET))
+            .extend(20.);
+        *visibility = Visibility::Visible;
+    }
+}
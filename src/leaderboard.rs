@@ -0,0 +1,307 @@
+use bevy::{prelude::*, window::ReceivedCharacter};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::GameAssets, modifiers::RunModifiers, replay::ReplayPlayback, save,
+    settings::GameSettings, AppState, GameManager, RunConfig,
+};
+
+pub const LEADERBOARD_SAVE_KEY: &str = "leaderboard";
+pub const LEADERBOARD_SIZE: usize = 10;
+pub const NAME_LENGTH: usize = 3;
+// Fills the letters the player didn't type, if they leave the game over screen early.
+pub const NAME_PLACEHOLDER: char = '?';
+
+pub const GAME_OVER_PANEL_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.flex_direction = FlexDirection::Column;
+    style.align_items = AlignItems::Center;
+    style.top = Val::Px(24.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+// Below the title and the menu button of the victory screen.
+pub const VICTORY_PANEL_TOP: Val = Val::Percent(40.);
+
+pub const NAME_ENTRY_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: usize,
+    pub date: NaiveDate,
+    /// Modifiers the run was played with, none for entries from before they existed.
+    #[serde(default)]
+    pub modifiers: RunModifiers,
+}
+
+/// Best local runs with the names of the players, sorted by score.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0
+            && (self.entries.len() < LEADERBOARD_SIZE
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    /// Adds the entry and drops the lowest ones over `LEADERBOARD_SIZE`.
+    pub fn insert(&mut self, entry: LeaderboardEntry) {
+        // Among equal scores, the older entry stays above.
+        let index = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(index, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+    }
+
+    pub fn to_text(&self) -> String {
+        if self.entries.is_empty() {
+            return "No runs yet".to_string();
+        }
+
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(place, entry)| {
+                let mut line = format!(
+                    "{}. {}  {}  {}",
+                    place + 1,
+                    entry.name,
+                    entry.score,
+                    entry.date.format("%Y-%m-%d")
+                );
+
+                if !entry.modifiers.0.is_empty() {
+                    line += &format!("  ({})", entry.modifiers.to_text());
+                }
+
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Name being typed for a qualifying score on the game over or victory screen.
+#[derive(Resource)]
+pub struct NameEntry {
+    pub name: String,
+    pub score: usize,
+    pub modifiers: RunModifiers,
+}
+
+#[derive(Component)]
+pub struct GameOverPanel;
+
+#[derive(Component)]
+pub struct NameEntryText;
+
+#[derive(Component)]
+pub struct LeaderboardText;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(save::load::<Leaderboard>(LEADERBOARD_SAVE_KEY).unwrap_or_default())
+            .add_systems(OnEnter(AppState::GameOver), spawn_game_over_panel)
+            .add_systems(OnEnter(AppState::Victory), spawn_game_over_panel)
+            .add_systems(
+                Update,
+                (type_name, update_leaderboard_text)
+                    .run_if(in_state(AppState::GameOver).or_else(in_state(AppState::Victory))),
+            )
+            .add_systems(
+                OnExit(AppState::GameOver),
+                (submit_unfinished_name, despawn_game_over_panel),
+            )
+            .add_systems(
+                OnExit(AppState::Victory),
+                (submit_unfinished_name, despawn_game_over_panel),
+            );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_game_over_panel(
+    mut commands: Commands,
+    leaderboard: Res<Leaderboard>,
+    game_manager: Res<GameManager>,
+    game_assets: Res<GameAssets>,
+    playback: Option<Res<ReplayPlayback>>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    run_modifiers: Res<RunModifiers>,
+    app_state: Res<State<AppState>>,
+) {
+    // The score of a replay is already on the board, freeplay can't lose
+    // and a debug starting score isn't earned.
+    let is_qualifying = playback.is_none()
+        && !settings.freeplay
+        && run_config.starting_score == 0
+        && leaderboard.qualifies(game_manager.score);
+
+    if is_qualifying {
+        commands.insert_resource(NameEntry {
+            name: String::new(),
+            score: game_manager.score,
+            modifiers: run_modifiers.clone(),
+        });
+    }
+
+    let mut panel_style = GAME_OVER_PANEL_STYLE;
+    if *app_state.get() == AppState::Victory {
+        panel_style.top = VICTORY_PANEL_TOP;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: panel_style,
+                ..default()
+            },
+            GameOverPanel,
+        ))
+        .with_children(|parent| {
+            if is_qualifying {
+                parent.spawn(TextBundle::from_section(
+                    "New high score! Type your name, Enter to save",
+                    TextStyle {
+                        font: game_assets.font.clone(),
+                        font_size: 28.0,
+                        color: Color::WHITE,
+                    },
+                ));
+
+                parent.spawn((
+                    TextBundle::from_section(
+                        name_entry_text(""),
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 64.0,
+                            color: NAME_ENTRY_COLOR,
+                        },
+                    ),
+                    NameEntryText,
+                ));
+            }
+
+            parent.spawn((
+                TextBundle::from_section(
+                    leaderboard.to_text(),
+                    TextStyle {
+                        font: game_assets.font.clone(),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                LeaderboardText,
+            ));
+        });
+}
+
+fn despawn_game_over_panel(
+    mut commands: Commands,
+    game_over_panel_query: Query<Entity, With<GameOverPanel>>,
+) {
+    for game_over_panel_entity in game_over_panel_query.iter() {
+        commands.entity(game_over_panel_entity).despawn_recursive();
+    }
+}
+
+fn name_entry_text(name: &str) -> String {
+    format!("{:_<width$}", name, width = NAME_LENGTH)
+}
+
+// Arcade-style name entry: letters and digits, Backspace to erase, Enter to save.
+fn type_name(
+    mut commands: Commands,
+    mut received_character_event_reader: EventReader<ReceivedCharacter>,
+    mut name_entry_text_query: Query<(Entity, &mut Text), With<NameEntryText>>,
+    name_entry: Option<ResMut<NameEntry>>,
+    mut leaderboard: ResMut<Leaderboard>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    let Some(mut name_entry) = name_entry else {
+        received_character_event_reader.clear();
+        return;
+    };
+
+    for received_character in received_character_event_reader.iter() {
+        let character = received_character.char.to_ascii_uppercase();
+
+        if character.is_ascii_alphanumeric() && name_entry.name.len() < NAME_LENGTH {
+            name_entry.name.push(character);
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Back) {
+        name_entry.name.pop();
+    }
+
+    let is_submitted = keyboard.just_pressed(KeyCode::Return) && !name_entry.name.is_empty();
+
+    if let Ok((name_entry_text_entity, mut text)) = name_entry_text_query.get_single_mut() {
+        if is_submitted {
+            commands.entity(name_entry_text_entity).despawn_recursive();
+        } else if name_entry.is_changed() {
+            text.sections[0].value = name_entry_text(&name_entry.name);
+        }
+    }
+
+    if is_submitted {
+        submit_name(&mut commands, &name_entry, &mut leaderboard);
+    }
+}
+
+fn submit_name(commands: &mut Commands, name_entry: &NameEntry, leaderboard: &mut Leaderboard) {
+    let mut name = name_entry.name.clone();
+    while name.len() < NAME_LENGTH {
+        name.push(NAME_PLACEHOLDER);
+    }
+
+    leaderboard.insert(LeaderboardEntry {
+        name,
+        score: name_entry.score,
+        date: Local::now().date_naive(),
+        modifiers: name_entry.modifiers.clone(),
+    });
+    save::save(LEADERBOARD_SAVE_KEY, leaderboard);
+
+    commands.remove_resource::<NameEntry>();
+}
+
+// Keeps the qualifying score, if the player replays before saving the name.
+fn submit_unfinished_name(
+    mut commands: Commands,
+    name_entry: Option<Res<NameEntry>>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    if let Some(name_entry) = name_entry {
+        submit_name(&mut commands, &name_entry, &mut leaderboard);
+    }
+}
+
+fn update_leaderboard_text(
+    mut leaderboard_text_query: Query<&mut Text, With<LeaderboardText>>,
+    leaderboard: Res<Leaderboard>,
+) {
+    if !leaderboard.is_changed() {
+        return;
+    }
+
+    for mut text in leaderboard_text_query.iter_mut() {
+        text.sections[0].value = leaderboard.to_text();
+    }
+}
@@ -1,57 +1,287 @@
+mod assets;
+mod balance;
+mod debug;
+mod focus;
+mod game_time;
+mod gravity_well;
+mod guide;
+mod leaderboard;
+mod menu;
+mod modifiers;
+mod particles;
+mod pause;
+mod planets;
+mod progress;
+mod replay;
+mod rewind;
+mod sandbox;
+mod save;
+mod settings;
+mod share;
+mod speedrun;
+mod stats;
 mod ui;
+mod warp;
 
-use std::{f32::consts::*, time::Duration};
+use std::{
+    collections::{HashSet, VecDeque},
+    f32::consts::*,
+    time::Duration,
+};
 
+use assets::{GameAssets, GameAssetsPlugin};
+use balance::{BalancePlugin, GameBalance};
 use bevy::{
     asset::LoadState,
     audio::{Volume, VolumeLevel},
     prelude::*,
-    window::PresentMode,
+    render::camera::{ScalingMode, Viewport},
+    window::{PresentMode, PrimaryWindow, WindowResized, WindowScaleFactorChanged},
+    winit::{UpdateMode, WinitSettings},
 };
 use bevy_tweening::{lens::TransformPositionLens, *};
+use debug::DebugPlugin;
+use focus::FocusPlugin;
+use game_time::{GameTime, GameTimePlugin};
+use gravity_well::GravityWellPlugin;
+use guide::AngleGuidePlugin;
+use leaderboard::LeaderboardPlugin;
+use menu::MenuPlugin;
+use modifiers::{RunModifier, RunModifiers};
 use parry2d::{
     math::Isometry,
-    query::contact,
-    shape::{Ball, Shape},
+    query::{contact, Contact},
+    shape::Ball,
 };
-use rand::Rng;
+use particles::{spawn_particle_burst, ParticlesPlugin};
+use pause::{PausePlugin, PauseState};
+use planets::{PlanetDefinitions, PlanetsPlugin};
+use progress::ProgressPlugin;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use replay::ReplayPlugin;
+use rewind::{Invincible, RewindPlugin, StateHistory};
+use sandbox::SandboxPlugin;
+use serde::{Deserialize, Serialize};
+use settings::{GameSettings, MusicTrack, SettingsPlugin};
+use share::SharePlugin;
+use speedrun::SpeedrunPlugin;
+use stats::{RunStats, StatsPlugin};
 use ui::{ReplayButton, ScoreText, UIPlugin};
+use warp::{Transitioning, WarpPlugin};
+
+// Size of the window at launch, the letterboxed camera keeps its framing.
+pub const WINDOW_SIZE: Vec2 = Vec2::new(840., 750.);
 
 pub const PLAYER_MOVEMENT_SPEED: f32 = 200.;
+// With the gravity below, a jump peaks at 450² / (2 * 752.4) ≈ 135 pixels after 0.6 seconds,
+// see `jump_peak_height`.
 pub const PLAYER_JUMP_STRENGTH: f32 = 450.;
-pub const GRAVITY_STRENGTH: f32 = -27.43;
+// Releasing the jump key while rising multiplies the upward speed by this, with the variable
+// jump setting. A tap peaks at 0.5² of the full height, about 34 instead of 135 pixels,
+// see `short_jump_peak_height`.
+pub const PLAYER_JUMP_CUT: f32 = 0.5;
+// Pixels per second squared, multiplied by `GameSettings::gravity_scale`.
+// These and the other tunables of `GameBalance` can be overridden in `balance.ron`.
+pub const GRAVITY_STRENGTH: f32 = -752.4;
 pub const PLAYER_FALL_ACCELERATION: f32 = -3000.;
+// Upward speed cap of the fast-rise, when the fast-fall key is inverted.
+pub const PLAYER_MAX_RISE_VELOCITY: f32 = 900.;
+// The run ends, when the player falls this far below the lowest planet.
+pub const PLAYER_FALL_LIMIT: f32 = 1000.;
 pub const PLAYER_START_POSITION: Vec3 = Vec3::new(0., PLANET_SIZE.y, 0.);
 pub const PLAYER_SIZE: Vec2 = Vec2::new(64., 64.);
+// The player is put on the first planet, if still airborne after this many times
+// the fall from the start should take. Holding the inverted fast-fall key can rise forever.
+pub const FIRST_LANDING_TIMEOUT_SCALE: f32 = 3.;
+// 25 degrees around the planet.
+pub const PLAYER_DASH_ANGLE: f32 = 0.436332;
+pub const PLAYER_DASH_COOLDOWN: f32 = 3.;
+// Vertical stretch of the sprite per unit of speed in the air, up to the max.
+pub const PLAYER_STRETCH_PER_VELOCITY: f32 = 0.0004;
+pub const PLAYER_MAX_STRETCH: f32 = 0.3;
+// Vertical squash of the sprite right after landing.
+pub const PLAYER_LANDING_SQUASH: f32 = 0.3;
+pub const PLAYER_SQUASH_SMOOTHING: f32 = 12.;
+
+// Collision tuning.
+// Shrinks the player collider below the sprite size, so transparent sprite corners don't collide.
+pub const PLAYER_COLLIDER_INSET: f32 = 4.;
+// Shrinks the obstacle collider below the sprite size. Larger is more forgiving.
+pub const OBSTACLE_COLLIDER_INSET: f32 = 6.;
+// The player stands on a planet within this distance, which keeps them grounded between frames.
+pub const PLANET_CONTACT_MARGIN: f32 = 1.;
+// Obstacles and their projectiles kill the player within this distance. 0 means actual overlap.
+pub const OBSTACLE_CONTACT_MARGIN: f32 = 0.;
 
 pub const PLANET_SIZE: Vec2 = Vec2::new(715., 715.);
 pub const PLANET_ROTATION_SPEED: f32 = 1.;
 pub const PLANET_SHRINK_SPEED: f32 = 50.; // b: 15.
+
+// Planet size when it vanishes on the normal difficulty.
 pub const PLANET_SHRINK_LIMIT: Vec2 = Vec2::new(200., 200.);
+// Fraction of the shrink speed taken away at the peak of a jump, with the airborne slowdown setting.
+pub const AIRBORNE_SHRINK_SLOWDOWN: f32 = 0.25;
+
+// Planets the player hasn't landed on yet are dimmed, the active one gets an outline.
+pub const INACTIVE_PLANET_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+pub const ACTIVE_PLANET_OUTLINE_COLOR: Color = Color::rgba(1., 1., 1., 0.5);
 
 pub const PLANET_FACE_SIZE: Vec2 = Vec2::new(715., 715.);
+// Face radius thresholds for `PLANET_SHRINK_LIMIT`, scaled for the other limits.
 pub const PLANET_FACE_NORMAL_THRESHOLD: f32 = 250.;
 pub const PLANET_FACE_BAD_THRESHOLD: f32 = 175.;
+// Radius before a face threshold, where the next face starts fading in over the current one.
+pub const PLANET_FACE_BLEND_RANGE: f32 = 30.;
 
 pub const OBSTACLE_SIZE: Vec2 = Vec2::new(64., 64.);
 pub const OBSTACLE_MOVEMENT_SPEED: f32 = 2.;
 pub const OBSTACLES_MAX_NUM: usize = 7;
-// 20 degrees - 45 degrees
-pub const OBSTACLE_CLOSE_GAP_RANGE: (f32, f32) = (0., 0.261799);
+// Planet circumference (in pixels) per obstacle in infinite mode, up to `GameBalance::obstacles_max_num`.
+// A full-sized planet fits 6, a tiny one 4, only the boss-sized planets reach the cap.
+pub const OBSTACLE_SURFACE_SPACING: f32 = 350.;
+// Chance of an obstacle orbiting against the others in infinite mode.
+pub const OBSTACLE_REVERSE_CHANCE: f64 = 0.3;
+// The first planet of a run gets a single obstacle this much slower, if the grace setting is on.
+pub const FIRST_PLANET_OBSTACLE_SPEED_SCALE: f32 = 0.6;
+// 10 degrees - 15 degrees, never 0, so a pushed obstacle always moves.
+pub const OBSTACLE_CLOSE_GAP_RANGE: (f32, f32) = (0.174533, 0.261799);
 // 40 degrees - 80 degrees
 pub const OBSTACLE_LONG_GAP_RANGE: (f32, f32) = (0.698132, 1.39626);
+// Time (in seconds) for new obstacles to fade in, after their texture is loaded.
+pub const OBSTACLE_FADE_IN_DURATION: f32 = 0.3;
+// Seconds each frame of an animated obstacle is shown, see `PlanetDef::obstacle_frames`.
+pub const OBSTACLE_ANIMATION_FRAME_TIME: f32 = 0.1;
+// Queued obstacles spawned every frame, with the staggered spawn setting on.
+pub const OBSTACLE_SPAWNS_PER_FRAME: usize = 1;
+// Opacity of the obstacles on the incoming planet, before the player lands on it.
+pub const DORMANT_OBSTACLE_ALPHA: f32 = 0.5;
+// 10 degrees, obstacles on a full-sized planet don't overlap.
+pub const OBSTACLE_MIN_GAP: f32 = 0.174533;
+// Obstacles of a `ClusterPattern::TightPairs` pair, 15 degrees apart.
+pub const OBSTACLE_PAIR_GAP: f32 = 0.261799;
+// Obstacles of a `ClusterPattern::BigGap` pack, 15 degrees apart.
+pub const OBSTACLE_PACKED_GAP: f32 = 0.261799;
+// Rerolls of an overlapping obstacle angle, before the obstacle is dropped.
+pub const OBSTACLE_PLACEMENT_ATTEMPTS: usize = 8;
 // 180 degrees
 pub const OBSTACLE_MAX_ANGLE_GENERATION: f32 = PI;
 // 45 degrees
 pub const OBSTACLE_MIN_ANGLE_GENERATION: f32 = FRAC_PI_4;
-
+// 180 degrees - 405 degrees, leaves the landing spot at 90 degrees free of obstacles.
+pub const OBSTACLE_FULL_ARC: (f32, f32) = (
+    OBSTACLE_MAX_ANGLE_GENERATION,
+    2. * PI + OBSTACLE_MIN_ANGLE_GENERATION,
+);
+
+// 70 degrees - 110 degrees, the shifted story patterns keep obstacles off the landing spot on top.
+pub const STORY_LANDING_CLEAR_ARC: (f32, f32) = (1.22173, 1.91986);
+
+// The trajectory arcs show where the obstacles are this many seconds ahead.
+pub const OBSTACLE_TRAJECTORY_TIME: f32 = 0.6;
+pub const OBSTACLE_TRAJECTORY_SEGMENTS: usize = 12;
+pub const OBSTACLE_TRAJECTORY_COLOR: Color = Color::rgba(1., 1., 1., 0.25);
+
+// Obstacles shooting at the player appear in infinite mode from this score.
+pub const SHOOTER_MIN_SCORE: usize = 12;
+pub const SHOOTER_CHANCE: f64 = 0.25;
+pub const SHOOTER_FIRE_INTERVAL: f32 = 2.5;
+pub const SHOOTER_COLOR: Color = Color::rgb(1., 0.55, 0.55);
+
+// Harmless look-alike obstacles appear in infinite mode from this score.
+pub const DECOY_MIN_SCORE: usize = 6;
+pub const DECOY_CHANCE: f64 = 0.1;
+pub const DECOY_COLOR: Color = Color::rgb(0.6, 0.8, 1.);
+
+pub const PROJECTILE_SIZE: Vec2 = Vec2::new(20., 20.);
+pub const PROJECTILE_SPEED: f32 = 250.;
+pub const PROJECTILE_LIFETIME: f32 = 4.;
+
+// Obstacles passing closer than this to the airborne player give bonus points.
+pub const NEAR_MISS_MARGIN: f32 = 24.;
+pub const NEAR_MISS_POINTS: usize = 1;
+
+// Landing on the next planet faster than this (in seconds) continues the combo.
+pub const COMBO_LANDING_TIME: f32 = 1.3;
+// Freeze frame after hitting an obstacle, before the game over screen.
+pub const HIT_STOP_DURATION: f32 = 0.08;
+pub const HIT_STOP_SHAKE_STRENGTH: f32 = 12.;
+// Sparks at the point where a hazard hit the player, and the tint of the hit player.
+pub const HIT_SPARK_COUNT: usize = 16;
+pub const HIT_SPARK_SPEED: (f32, f32) = (150., 400.);
+pub const HIT_SPARK_COLOR: Color = Color::rgb(1., 0.85, 0.3);
+pub const PLAYER_HIT_COLOR: Color = Color::rgb(1., 0.3, 0.3);
+// Invincibility after losing one of several lives, so a cluster of obstacles takes only one.
+pub const LIFE_LOST_INVINCIBILITY_DURATION: f32 = 1.;
+
+// One is picked at random for each jump. The first is also the combo break sound.
+pub const JUMP_SOUNDS: [&str; 3] = [
+    "sounds/350905__cabled_mess__jump_c_05.ogg",
+    "sounds/jump_blip_low.wav",
+    "sounds/jump_blip_high.wav",
+];
+// Random playback speed change of the jump sounds, both ways.
+pub const JUMP_SOUND_PITCH_VARIATION: f32 = 0.1;
+
+// Fraction of the viewport the camera looks ahead, while falling to the next planet.
+pub const CAMERA_LOOK_AHEAD: f32 = 0.08;
+pub const CAMERA_LOOK_AHEAD_SMOOTHING: f32 = 3.;
+// How fast the rotating camera turns to the player, see `GameSettings::rotate_camera`.
+pub const CAMERA_ROTATION_SMOOTHING: f32 = 8.;
+// With the camera follow setting, the airborne player stays this far (in pixels) below the top edge.
+pub const CAMERA_FOLLOW_MARGIN: f32 = 120.;
+// Largest shift of the view by the camera follow, as a part of the view height.
+pub const CAMERA_FOLLOW_MAX_OFFSET: f32 = 0.35;
+
+// Color of the letterbox bars. The whole window is cleared with it, the camera viewport
+// draws over the middle.
+pub const LETTERBOX_COLOR: Color = Color::BLACK;
 pub const BACKGROUND_SIZE: Vec2 = Vec2::new(1000., 1000.);
+// Music volume at 100% in the settings.
+pub const MUSIC_BASE_VOLUME: f32 = 0.25;
+
 pub const BACKGROUND_SPEED: f32 = 100.;
+// Time (in seconds) of the camera move to the next planet.
+pub const PLANET_TRANSITION_DURATION: f32 = 1.2;
+// Longest frame time the gameplay simulates at once, so a stutter can't teleport the player
+// through a planet or shrink it in a single step.
+pub const MAX_GAMEPLAY_DELTA: f32 = 1. / 30.;
+// Longest wait between the redraws of the menus and end screens with the frame limit setting.
+// Input still wakes them up right away.
+pub const MENU_FRAME_TIME: f32 = 1. / 30.;
+// Seconds the planet rotation takes to speed up after the landing, or to stop.
+pub const PLANET_ROTATION_EASE_DURATION: f32 = 0.6;
 
 #[derive(Resource, Default)]
 struct GameManager {
     infinite_mode: bool,
     score: usize,
+    // Part of the score from near misses. Doesn't count toward the score thresholds,
+    // so skimming the obstacles doesn't bring the harder ones sooner.
+    bonus_score: usize,
+    planets_cleared: usize,
+    lives: u8,
+    // Turn of the story patterns with the shifted story setting, rolled on the first planet.
+    story_angle_offset: Option<f32>,
+}
+
+impl GameManager {
+    /// Score the difficulty thresholds compare against, without the near miss bonus.
+    fn progress_score(&self) -> usize {
+        self.score - self.bonus_score
+    }
+}
+
+/// Counts planets the player reached quickly, after the previous one vanished.
+#[derive(Resource, Default)]
+pub struct Combo {
+    pub count: usize,
+}
+
+/// How much the view is shifted up to keep the airborne player on screen,
+/// as a part of the view height.
+#[derive(Resource, Default)]
+pub struct CameraFollow {
+    pub offset: f32,
 }
 
 /// Resource for tracking loading assets.
@@ -70,19 +300,79 @@ pub enum LoadingState {
 #[derive(States, Debug, Default, Clone, Eq, PartialEq, Hash)]
 pub enum AppState {
     #[default]
+    Menu,
     Playing,
     GameOver,
+    /// The short run reached its planet target.
+    Victory,
+    /// Obstacle pattern editor, see `SandboxPlugin`. Only in debug builds.
+    Sandbox,
 }
 
 #[derive(Component)]
 struct Player {
     pub is_grounded: bool,
     velocity: f32,
+    dash_cooldown: Timer,
 }
 
 #[derive(Component)]
 struct Obstacle {
     angle: f32,
+    // Radians per second, negative orbits clockwise.
+    angular_velocity: f32,
+    // Whether the sprite turns with the orbit, see `PlanetDef::obstacle_rotates`.
+    rotates: bool,
+    // Decoys are drawn with `DECOY_COLOR` and pass through the player.
+    lethal: bool,
+}
+
+/// Sprite sheet cycling through its `frames` left to right.
+/// Only the look changes, the collider stays the same.
+#[derive(Component)]
+struct AnimatedSprite {
+    timer: Timer,
+    frames: usize,
+}
+
+/// Obstacle that is still appearing. It's harmless until fully visible.
+#[derive(Component)]
+struct ObstacleFadeIn {
+    timer: Timer,
+}
+
+/// Obstacle decided by `spawn_obstacles`, waiting in the `ObstacleSpawnQueue`.
+struct PendingObstacle {
+    planet: Entity,
+    angle: f32,
+    angular_velocity: f32,
+    rotates: bool,
+    is_shooter: bool,
+    is_decoy: bool,
+    texture: Handle<Image>,
+    frames: usize,
+}
+
+/// Obstacles to spawn over the next frames, which spreads out the spawn cost.
+#[derive(Resource, Default)]
+struct ObstacleSpawnQueue {
+    pending: VecDeque<PendingObstacle>,
+}
+
+/// Obstacle waiting on the incoming planet. It's dimmed, still and harmless until the player lands.
+#[derive(Component)]
+struct ObstacleDormant;
+
+/// Obstacle that periodically fires projectiles at the player.
+#[derive(Component)]
+struct Shooter {
+    fire_timer: Timer,
+}
+
+#[derive(Component)]
+struct Projectile {
+    velocity: Vec2,
+    lifetime: Timer,
 }
 
 #[derive(Component)]
@@ -91,9 +381,28 @@ struct Planet {
     is_playing: bool,
     obstacles: Vec<Entity>,
     radius: f32,
+    // Obstacle wave, see `PlanetDef::waves`. Always 0 in infinite mode.
+    wave: usize,
+    // Obstacles of the waves that are over, they count as dodged along with the last one.
+    wave_obstacles_dodged: usize,
+    // Goes from 0 to 1 while the planet plays and back otherwise, see `Planet::rotation_factor`.
+    rotation_ease: f32,
+}
+
+impl Planet {
+    /// Part of the full rotation speed the planet turns with, eased in and out.
+    fn rotation_factor(&self) -> f32 {
+        let ease = self.rotation_ease;
+        ease * ease * (3. - 2. * ease)
+    }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+/// Planet the player got through. It spins down and shrinks away before it despawns,
+/// see `retire_cleared_planets`, nothing lands on it or spawns on it anymore.
+#[derive(Component)]
+struct ClearedPlanet;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum PlanetVariant {
     Earth = 0,
     Venus,
@@ -102,10 +411,36 @@ pub enum PlanetVariant {
     Jupiter,
     Neptune,
     Uran,
+    Boss,
 }
 
 impl PlanetVariant {
-    fn next(self) -> PlanetVariant {
+    pub const ALL: [PlanetVariant; 8] = [
+        PlanetVariant::Earth,
+        PlanetVariant::Venus,
+        PlanetVariant::Mars,
+        PlanetVariant::Mercury,
+        PlanetVariant::Jupiter,
+        PlanetVariant::Neptune,
+        PlanetVariant::Uran,
+        PlanetVariant::Boss,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PlanetVariant::Earth => "Earth",
+            PlanetVariant::Venus => "Venus",
+            PlanetVariant::Mars => "Mars",
+            PlanetVariant::Mercury => "Mercury",
+            PlanetVariant::Jupiter => "Jupiter",
+            PlanetVariant::Neptune => "Neptune",
+            PlanetVariant::Uran => "Uranus",
+            PlanetVariant::Boss => "The Giant",
+        }
+    }
+
+    // The boss comes once, after the last story planet. Infinite mode cycles without it.
+    fn next(self, infinite_mode: bool) -> PlanetVariant {
         match self {
             PlanetVariant::Earth => PlanetVariant::Venus,
             PlanetVariant::Venus => PlanetVariant::Mars,
@@ -113,59 +448,30 @@ impl PlanetVariant {
             PlanetVariant::Mercury => PlanetVariant::Jupiter,
             PlanetVariant::Jupiter => PlanetVariant::Neptune,
             PlanetVariant::Neptune => PlanetVariant::Uran,
-            PlanetVariant::Uran => PlanetVariant::Earth,
+            PlanetVariant::Uran if infinite_mode => PlanetVariant::Earth,
+            PlanetVariant::Uran => PlanetVariant::Boss,
+            PlanetVariant::Boss => PlanetVariant::Earth,
         }
     }
-
-    // For story mode
-    fn get_obstacles(self) -> Vec<f32> {
-        let mut angles = Vec::new();
-        match self {
-            PlanetVariant::Earth => {
-                angles.extend([0.]);
-            }
-            PlanetVariant::Venus => {
-                angles.extend([0., PI]);
-            }
-            PlanetVariant::Mars => {
-                angles.extend([
-                    290f32.to_radians(),
-                    270f32.to_radians(),
-                    250f32.to_radians(),
-                ]);
-            }
-            PlanetVariant::Mercury => {
-                angles.extend([PI, 30f32.to_radians(), 0., 330f32.to_radians()]);
-            }
-            PlanetVariant::Jupiter => {
-                angles.extend([FRAC_PI_6, 150f32.to_radians(), 270f32.to_radians()]);
-            }
-            PlanetVariant::Neptune => {
-                angles.extend([
-                    FRAC_PI_4,
-                    FRAC_PI_6,
-                    15f32.to_radians(),
-                    240f32.to_radians(),
-                    225f32.to_radians(),
-                    210f32.to_radians(),
-                ]);
-            }
-            PlanetVariant::Uran => {
-                angles.extend([PI, 225f32.to_radians(), 315f32.to_radians(), 0.]);
-            }
-        };
-        return angles;
-    }
 }
 
 #[derive(Component)]
 struct Background;
 
+#[derive(Component)]
+struct Music {
+    track: MusicTrack,
+}
+
 #[derive(Component)]
 struct PlanetFace {
     face: PlanetFaceState,
 }
 
+/// The next face of the planet, fading in on top of the `PlanetFace` near a threshold.
+#[derive(Component)]
+struct PlanetFaceOverlay;
+
 /// Abstraction of the parry2d shapes to store in the component.
 #[derive(Component, Clone)]
 pub struct Collider {
@@ -178,6 +484,154 @@ pub struct PlanetSpawnEvent {
     last_planet_position: Vec3,
 }
 
+/// Sent when the player first lands on a planet and it starts shrinking.
+#[derive(Event)]
+pub struct PlanetActivatedEvent {
+    pub planet: Entity,
+    pub variant: PlanetVariant,
+}
+
+/// Sent when the active planet shrinks away and the next one spawns.
+#[derive(Event)]
+pub struct PlanetClearedEvent {
+    pub variant: PlanetVariant,
+    /// Obstacles of every wave of the planet.
+    pub obstacles: usize,
+}
+
+pub enum ScoreReason {
+    PlanetCleared,
+    /// An obstacle passed close to the airborne player.
+    NearMiss,
+}
+
+#[derive(Event)]
+pub struct ScoreEvent {
+    pub points: usize,
+    pub reason: ScoreReason,
+}
+
+/// Options of the current run, taken from the settings when it starts.
+#[derive(Resource, Default)]
+pub struct RunConfig {
+    /// The run ends with a victory after clearing this many planets.
+    pub target_planets: Option<usize>,
+    /// The run ends with a victory after the boss planet, instead of going on in infinite mode.
+    pub victory_after_story: bool,
+    /// Score the run starts with, picked in the debug panel to try the late game.
+    /// Such runs don't go on the leaderboard.
+    pub starting_score: usize,
+}
+
+/// Multiplier of `GameBalance::planet_shrink_speed`, updated every frame by `update_planet_shrink_rate`.
+#[derive(Resource)]
+pub struct PlanetShrinkRate(pub f32);
+
+impl Default for PlanetShrinkRate {
+    fn default() -> Self {
+        PlanetShrinkRate(1.)
+    }
+}
+
+/// The planet the player landed on and shrinking, `None` between planets.
+/// Set on landing by `check_player_planet_collisions`, the radius follows `shrink_current_planet`.
+#[derive(Resource)]
+pub struct CurrentPlanet {
+    pub entity: Option<Entity>,
+    pub variant: PlanetVariant,
+    pub radius: f32,
+}
+
+impl Default for CurrentPlanet {
+    fn default() -> Self {
+        CurrentPlanet {
+            entity: None,
+            variant: PlanetVariant::Earth,
+            radius: PLANET_SIZE.y / 2.,
+        }
+    }
+}
+
+/// Seed typed in the menu for the next run, random if `None`. It's used once.
+#[derive(Resource, Default)]
+pub struct NextRunSeed(pub Option<u64>);
+
+/// Random numbers of the gameplay, from a seed that's saved with the replay.
+/// Effects and sounds keep using `rand::thread_rng`, so they can't throw a replay off.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::from_seed(rand::random())
+    }
+}
+
+/// Present while the gameplay is frozen on impact. The run ends once the timer finishes.
+#[derive(Resource)]
+pub struct HitStop(pub Timer);
+
+/// Why the last run ended, shown on the game over screen.
+#[derive(Resource, Clone, Copy, Debug)]
+pub enum DeathCause {
+    ObstacleHit,
+    ProjectileHit,
+    FellIntoVoid,
+    /// Ended with `FORCE_GAME_OVER_KEY` in debug builds.
+    Forced,
+}
+
+impl DeathCause {
+    pub fn message(self) -> &'static str {
+        match self {
+            DeathCause::ObstacleHit => "Hit by an obstacle!",
+            DeathCause::ProjectileHit => "Shot down by a projectile!",
+            DeathCause::FellIntoVoid => "Missed the landing!",
+            DeathCause::Forced => "Ended from the debug keys!",
+        }
+    }
+}
+
+/// Sent when a hazard gets the player. `handle_player_hit` decides, whether the run ends.
+#[derive(Event)]
+pub struct PlayerHitEvent {
+    pub cause: DeathCause,
+    /// Where the hazard touched the player, `None` for falling into the void.
+    pub contact_point: Option<Vec2>,
+}
+
+/// Sent once per landing, when the player touches a planet after being airborne.
+#[derive(Event)]
+pub struct PlayerLandedEvent {
+    pub planet: Entity,
+    pub position: Vec3,
+}
+
+/// Sent when the player jumps off a planet.
+#[derive(Event)]
+pub struct PlayerJumpedEvent {
+    pub position: Vec3,
+}
+
+/// Sent when the player reaches the next planet too slowly and loses the combo.
+#[derive(Event)]
+pub struct ComboBrokenEvent {
+    pub lost_combo: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PlanetFaceState {
     Good,
     Normal,
@@ -189,7 +643,7 @@ fn main() {
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Indie Varvar's 2023".into(),
-                resolution: (840., 750.).into(),
+                resolution: (WINDOW_SIZE.x, WINDOW_SIZE.y).into(),
                 present_mode: PresentMode::AutoVsync,
                 // mode: WindowMode::BorderlessFullscreen,
                 // Tells wasm to resize the window according to the available canvas
@@ -201,27 +655,144 @@ fn main() {
             ..default()
         }))
         .add_plugins(TweeningPlugin)
+        .add_plugins(GameAssetsPlugin)
+        .add_plugins(BalancePlugin)
+        .add_plugins(PlanetsPlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(ProgressPlugin)
+        .add_plugins(LeaderboardPlugin)
         .add_plugins(UIPlugin)
+        .add_plugins(MenuPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(GameTimePlugin)
+        .add_plugins(DebugPlugin)
+        .add_plugins(WarpPlugin)
+        .add_plugins(StatsPlugin)
+        .add_plugins(FocusPlugin)
+        .add_plugins(RewindPlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(ParticlesPlugin)
+        .add_plugins(SandboxPlugin)
+        .add_plugins(SpeedrunPlugin)
+        .add_plugins(SharePlugin)
+        .add_plugins(AngleGuidePlugin)
+        .add_plugins(GravityWellPlugin)
         .add_event::<PlanetSpawnEvent>()
+        .add_event::<PlanetActivatedEvent>()
+        .add_event::<ComboBrokenEvent>()
+        .add_event::<ScoreEvent>()
+        .add_event::<PlanetClearedEvent>()
+        .add_event::<PlayerLandedEvent>()
+        .add_event::<PlayerJumpedEvent>()
+        .add_event::<PlayerHitEvent>()
         .add_state::<LoadingState>()
         .add_state::<AppState>()
         .init_resource::<AssetsLoading>()
         .init_resource::<GameManager>()
+        .init_resource::<Combo>()
+        .init_resource::<CameraFollow>()
+        .init_resource::<RunConfig>()
+        .init_resource::<RunModifiers>()
+        .init_resource::<GameRng>()
+        .init_resource::<NextRunSeed>()
+        .init_resource::<ObstacleSpawnQueue>()
+        .init_resource::<CurrentPlanet>()
+        .init_resource::<PlanetShrinkRate>()
         .add_systems(Startup, (spawn_2d_camera, spawn_background))
+        .insert_resource(ClearColor(LETTERBOX_COLOR))
+        .add_systems(
+            Update,
+            (
+                switch_music_track,
+                apply_music_volume,
+                apply_letterbox,
+                apply_frame_limit,
+            ),
+        )
         .add_systems(OnEnter(AppState::Playing), (start_game, spawn_player))
         .add_systems(
             Update,
             (
                 rotate_planets,
-                shrink_current_planet,
+                retire_cleared_planets.after(rotate_planets),
+                shrink_current_planet.before(apply_score_events),
                 player_jump.run_if(in_state(LoadingState::None)),
-                show_gizmos,
+                player_dash
+                    .after(check_player_planet_collisions)
+                    .run_if(in_state(LoadingState::None)),
                 check_player_planet_collisions
                     .after(player_jump)
                     .run_if(in_state(LoadingState::None)),
                 move_obstacles_on_planet,
+                fade_in_obstacles.run_if(in_state(LoadingState::None)),
                 check_player_obstacle_collisions,
+                fire_projectiles,
+                move_projectiles,
+                check_player_projectile_collisions.after(move_projectiles),
+                check_player_out_of_bounds,
                 manage_planet_face,
+                camera_follow,
+                camera_look_ahead.after(camera_follow),
+                rotate_camera.after(player_dash),
+                track_combo,
+                apply_score_events,
+            )
+                .run_if(in_state(AppState::Playing))
+                .run_if(in_state(PauseState::Running))
+                .run_if(not(resource_exists::<HitStop>())),
+        )
+        .add_systems(
+            Update,
+            handle_player_hit
+                .after(check_player_obstacle_collisions)
+                .after(check_player_projectile_collisions)
+                .after(check_player_out_of_bounds)
+                .run_if(in_state(AppState::Playing))
+                .run_if(in_state(PauseState::Running))
+                .run_if(not(resource_exists::<HitStop>())),
+        )
+        .add_systems(
+            Update,
+            spawn_queued_obstacles
+                .run_if(in_state(AppState::Playing))
+                .run_if(in_state(LoadingState::None)),
+        )
+        .add_systems(
+            Update,
+            update_planet_shrink_rate
+                .after(check_player_planet_collisions)
+                .before(shrink_current_planet)
+                .before(manage_planet_face)
+                .run_if(in_state(AppState::Playing))
+                .run_if(in_state(PauseState::Running))
+                .run_if(not(resource_exists::<HitStop>())),
+        )
+        .add_systems(
+            Update,
+            (
+                wake_dormant_obstacles,
+                squash_and_stretch_player,
+                ensure_first_landing,
+                animate_sprites,
+            )
+                .after(check_player_planet_collisions)
+                .run_if(in_state(AppState::Playing))
+                .run_if(in_state(PauseState::Running))
+                .run_if(not(resource_exists::<HitStop>())),
+        )
+        .add_systems(
+            Update,
+            tick_hit_stop
+                .run_if(in_state(AppState::Playing))
+                .run_if(in_state(PauseState::Running))
+                .run_if(resource_exists::<HitStop>()),
+        )
+        .add_systems(
+            Update,
+            (
+                show_gizmos,
+                highlight_active_planet,
+                draw_obstacle_trajectories,
             )
                 .run_if(in_state(AppState::Playing)),
         )
@@ -232,9 +803,14 @@ fn main() {
                 check_obstacles_loading.run_if(in_state(LoadingState::Obstacles)),
             ),
         )
-        .add_systems(OnEnter(LoadingState::Planet), spawn_planet)
+        // The obstacles come with the planet, so the player sees them during the approach.
+        .add_systems(
+            OnEnter(LoadingState::Planet),
+            (spawn_planet, apply_deferred, spawn_obstacles).chain(),
+        )
         .add_systems(OnEnter(LoadingState::Obstacles), spawn_obstacles)
         .add_systems(OnEnter(AppState::GameOver), restart_game)
+        .add_systems(OnEnter(AppState::Victory), restart_game)
         .run();
 }
 
@@ -264,15 +840,147 @@ fn spawn_2d_camera(mut commands: Commands) {
     ));
 }
 
+/// Largest part of the window in the middle with the `aspect_ratio`, in physical pixels.
+fn letterbox_viewport(window: &Window, aspect_ratio: f32) -> Option<Viewport> {
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+
+    // Minimized windows have no size.
+    if window_size.x == 0 || window_size.y == 0 {
+        return None;
+    }
+
+    let window_ratio = window_size.x as f32 / window_size.y as f32;
+    let viewport_size = if window_ratio > aspect_ratio {
+        // Pillarbox
+        UVec2::new(
+            (window_size.y as f32 * aspect_ratio).round() as u32,
+            window_size.y,
+        )
+    } else {
+        // Letterbox
+        UVec2::new(
+            window_size.x,
+            (window_size.x as f32 / aspect_ratio).round() as u32,
+        )
+    };
+
+    Some(Viewport {
+        physical_position: (window_size - viewport_size) / 2,
+        physical_size: viewport_size.max(UVec2::ONE),
+        ..default()
+    })
+}
+
+// Fits the camera viewport to the letterbox setting, whenever it or the window changes.
+// The letterboxed view always spans the height of the launch window, so it shows the same
+// part of the scene at any window size, instead of cropping it.
+fn apply_letterbox(
+    mut camera_query: Query<(&mut Camera, &mut OrthographicProjection)>,
+    mut window_resized_event_reader: EventReader<WindowResized>,
+    mut scale_factor_event_reader: EventReader<WindowScaleFactorChanged>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<GameSettings>,
+) {
+    // Both readers are drained, so old events don't pile up.
+    let window_resized = window_resized_event_reader.iter().count() > 0;
+    let scale_factor_changed = scale_factor_event_reader.iter().count() > 0;
+
+    if !window_resized && !scale_factor_changed && !settings.is_changed() {
+        return;
+    }
+
+    let (Ok((mut camera, mut camera_projection)), Ok(window)) =
+        (camera_query.get_single_mut(), window_query.get_single())
+    else {
+        return;
+    };
+
+    let viewport = settings
+        .letterbox
+        .and_then(|aspect_ratio| letterbox_viewport(window, aspect_ratio.ratio()));
+    let scaling_mode = match (settings.letterbox, &viewport) {
+        (Some(aspect_ratio), Some(_)) => ScalingMode::Fixed {
+            width: WINDOW_SIZE.y * aspect_ratio.ratio(),
+            height: WINDOW_SIZE.y,
+        },
+        _ => ScalingMode::WindowSize(1.),
+    };
+
+    if camera.viewport != viewport {
+        camera.viewport = viewport;
+    }
+
+    camera_projection.scaling_mode = scaling_mode;
+}
+
+// Runs at full speed during play, and only as fast as needed everywhere else.
+fn apply_frame_limit(
+    mut winit_settings: ResMut<WinitSettings>,
+    app_state: Res<State<AppState>>,
+    pause_state: Res<State<PauseState>>,
+    settings: Res<GameSettings>,
+) {
+    if !app_state.is_changed() && !pause_state.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    let is_idle = match app_state.get() {
+        AppState::Playing => *pause_state.get() == PauseState::Paused,
+        AppState::Sandbox => false,
+        AppState::Menu | AppState::GameOver | AppState::Victory => true,
+    };
+
+    *winit_settings = if is_idle && settings.menu_frame_limit {
+        let max_wait = Duration::from_secs_f32(MENU_FRAME_TIME);
+        WinitSettings {
+            focused_mode: UpdateMode::Reactive { max_wait },
+            unfocused_mode: UpdateMode::ReactiveLowPower { max_wait },
+            ..default()
+        }
+    } else {
+        WinitSettings::game()
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
 fn start_game(
+    mut commands: Commands,
     mut planet_spawn_event_writer: EventWriter<PlanetSpawnEvent>,
     mut next_loading_state: ResMut<NextState<LoadingState>>,
     mut game_manager: ResMut<GameManager>,
+    mut combo: ResMut<Combo>,
+    mut run_stats: ResMut<RunStats>,
+    mut run_config: ResMut<RunConfig>,
+    mut game_rng: ResMut<GameRng>,
+    mut next_run_seed: ResMut<NextRunSeed>,
+    invincible_query: Query<Entity, With<Invincible>>,
+    settings: Res<GameSettings>,
 ) {
     next_loading_state.set(LoadingState::Planet);
 
+    *game_rng = match next_run_seed.0.take() {
+        Some(seed) => GameRng::from_seed(seed),
+        None => GameRng::default(),
+    };
+    info!("Run seed: {}", game_rng.seed);
+
+    run_config.target_planets = settings.short_run_planets;
+    run_config.victory_after_story = !settings.endless_after_story;
+
     game_manager.infinite_mode = false;
-    game_manager.score = 0;
+    game_manager.score = run_config.starting_score;
+    game_manager.bonus_score = 0;
+    game_manager.planets_cleared = 0;
+    game_manager.lives = settings.difficulty.lives();
+    game_manager.story_angle_offset = None;
+    combo.count = 0;
+    *run_stats = RunStats::default();
+    // In case the last run was left during the hit-stop.
+    commands.remove_resource::<HitStop>();
+
+    for player_entity in invincible_query.iter() {
+        commands.entity(player_entity).remove::<Invincible>();
+    }
 
     planet_spawn_event_writer.send(PlanetSpawnEvent {
         planet_variant_to_spawn: PlanetVariant::Earth,
@@ -283,12 +991,12 @@ fn start_game(
 // TODO: fix bug with invisible obstacle after restart.
 fn restart_game(
     mut commands: Commands,
-    mut camera_query: Query<&mut Transform, With<Camera>>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
     mut background_query: Query<&mut Transform, (With<Background>, Without<Camera>)>,
     despawn_entities: Query<
         Entity,
         (
-            Or<(With<Planet>, With<Obstacle>, With<Player>)>,
+            Or<(With<Planet>, With<Obstacle>, With<Player>, With<Projectile>)>,
             (
                 Without<Camera>,
                 Without<ReplayButton>,
@@ -298,13 +1006,17 @@ fn restart_game(
         ),
     >,
 ) {
-    println!("Len: {}", despawn_entities.iter().len());
+    commands.insert_resource(CurrentPlanet::default());
+
+    debug!("Despawning {} entities", despawn_entities.iter().len());
     for entity_to_despawn in despawn_entities.iter() {
         commands.entity(entity_to_despawn).despawn_recursive();
     }
 
-    if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+    if let Ok((mut camera_transform, mut camera_projection)) = camera_query.get_single_mut() {
         camera_transform.translation = PLAYER_START_POSITION;
+        camera_transform.rotation = Quat::IDENTITY;
+        camera_projection.viewport_origin = Vec2::splat(0.5);
     }
 
     if let Ok(mut background_transform) = background_query.get_single_mut() {
@@ -312,34 +1024,34 @@ fn restart_game(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_planet(
     mut planet_spawn_event_reader: EventReader<PlanetSpawnEvent>,
     mut commands: Commands,
-    mut camera_query: Query<(&Transform, &mut Animator<Transform>), With<Camera>>,
+    mut camera_query: Query<(&mut Transform, &mut Animator<Transform>), With<Camera>>,
     mut loading: ResMut<AssetsLoading>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut background_query: Query<
-        (&mut Animator<Transform>, &Transform),
+        (&mut Animator<Transform>, &mut Transform),
         (With<Background>, Without<Camera>),
     >,
     asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+    planet_definitions: Res<PlanetDefinitions>,
+    run_modifiers: Res<RunModifiers>,
 ) {
     for planet_spawn_event in planet_spawn_event_reader.iter() {
-        let texture = match planet_spawn_event.planet_variant_to_spawn {
-            PlanetVariant::Earth => asset_server.load("art/Earth.png"),
-            PlanetVariant::Mars => asset_server.load("art/Mars.png"),
-            PlanetVariant::Venus => asset_server.load("art/Venus.png"),
-            PlanetVariant::Mercury => asset_server.load("art/Mercury.png"),
-            PlanetVariant::Jupiter => asset_server.load("art/Jupiter.png"),
-            PlanetVariant::Neptune => asset_server.load("art/Neptune.png"),
-            PlanetVariant::Uran => asset_server.load("art/Uran.png"),
-        };
+        let planet_def = planet_definitions.get(planet_spawn_event.planet_variant_to_spawn);
+        let texture = asset_server.load(planet_def.texture.as_str());
 
         let mut new_planet_position = planet_spawn_event.last_planet_position;
         new_planet_position.y -= PLANET_SIZE.y * 2.;
 
         // Create planet collider
-        let planet_radius = PLANET_SIZE.y / 2.0;
+        let planet_size = run_modifiers.planet_size(planet_def);
+        // The face keeps its size relative to the planet with the tiny planets modifier.
+        let face_size = PLANET_FACE_SIZE * planet_size.y / planet_def.size;
+        let planet_radius = planet_size.y / 2.0;
         let collider_shape = Ball::new(planet_radius);
 
         commands
@@ -348,7 +1060,8 @@ fn spawn_planet(
                     transform: Transform::from_translation(new_planet_position),
                     texture: texture.clone(),
                     sprite: Sprite {
-                        custom_size: Some(PLANET_SIZE),
+                        custom_size: Some(planet_size),
+                        color: INACTIVE_PLANET_COLOR,
                         ..default()
                     },
                     ..default()
@@ -357,7 +1070,10 @@ fn spawn_planet(
                     variant: planet_spawn_event.planet_variant_to_spawn,
                     is_playing: false,
                     obstacles: Vec::new(),
-                    radius: PLANET_SIZE.y / 2.,
+                    radius: planet_radius,
+                    wave: 0,
+                    wave_obstacles_dodged: 0,
+                    rotation_ease: 0.,
                 },
                 Collider {
                     shape: collider_shape,
@@ -373,10 +1089,10 @@ fn spawn_planet(
                     SpriteSheetBundle {
                         sprite: TextureAtlasSprite {
                             index: 0,
-                            custom_size: Some(PLANET_FACE_SIZE),
+                            custom_size: Some(face_size),
                             ..default()
                         },
-                        texture_atlas: texture_atlas_handle,
+                        texture_atlas: texture_atlas_handle.clone(),
                         transform: Transform::from_xyz(0., 0., 10.),
                         ..default()
                     },
@@ -384,10 +1100,49 @@ fn spawn_planet(
                         face: PlanetFaceState::Good,
                     },
                 ));
+
+                parent.spawn((
+                    SpriteSheetBundle {
+                        sprite: TextureAtlasSprite {
+                            index: 1,
+                            custom_size: Some(face_size),
+                            color: Color::WHITE.with_a(0.),
+                            ..default()
+                        },
+                        texture_atlas: texture_atlas_handle,
+                        transform: Transform::from_xyz(0., 0., 11.),
+                        ..default()
+                    },
+                    PlanetFaceOverlay,
+                ));
             });
 
         loading.0.push(texture.clone_untyped());
 
+        if settings.instant_camera_cut {
+            if let Ok((mut camera_transform, mut camera_animator)) = camera_query.get_single_mut() {
+                cut_to(
+                    &mut camera_transform,
+                    &mut camera_animator,
+                    new_planet_position,
+                );
+            }
+
+            if let Ok((mut background_animator, mut bg_transform)) =
+                background_query.get_single_mut()
+            {
+                let background_position =
+                    Vec3::new(0., new_planet_position.y, bg_transform.translation.z);
+                cut_to(
+                    &mut bg_transform,
+                    &mut background_animator,
+                    background_position,
+                );
+            }
+
+            continue;
+        }
+
         // Tween camera position
         if let Ok((camera_transform, mut camera_animator)) = camera_query.get_single_mut() {
             // // camera_transform.translation = new_planet_position;
@@ -397,7 +1152,7 @@ fn spawn_planet(
                 EaseFunction::QuadraticInOut,
                 // Animation time (one way only; for ping-pong it takes 2 seconds
                 // to come back to start).
-                Duration::from_secs_f32(1.2),
+                Duration::from_secs_f32(PLANET_TRANSITION_DURATION),
                 // The lens gives the Animator access to the Transform component,
                 // to animate it. It also contains the start and end values associated
                 // with the animation ratios 0. and 1.
@@ -410,11 +1165,22 @@ fn spawn_planet(
             camera_animator.set_tweenable(tween);
         }
 
+        // With the warp effect, the steeper ease makes the background rush past mid-transition.
+        let background_ease = if settings.warp_effect {
+            commands.insert_resource(Transitioning(Timer::from_seconds(
+                PLANET_TRANSITION_DURATION,
+                TimerMode::Once,
+            )));
+            EaseFunction::ExponentialInOut
+        } else {
+            EaseFunction::QuadraticInOut
+        };
+
         // Tween background position
         if let Ok((mut background_animator, bg_transform)) = background_query.get_single_mut() {
             let tween = Tween::new(
-                EaseFunction::QuadraticInOut,
-                Duration::from_secs_f32(1.2),
+                background_ease,
+                Duration::from_secs_f32(PLANET_TRANSITION_DURATION),
                 TransformPositionLens {
                     start: bg_transform.translation,
                     end: Vec3::new(0., new_planet_position.y, bg_transform.translation.z),
@@ -426,20 +1192,46 @@ fn spawn_planet(
     }
 }
 
+// Moves the entity right away. The tween of a transition still running is replaced
+// by an empty one, so it doesn't pull the entity back.
+fn cut_to(transform: &mut Transform, animator: &mut Animator<Transform>, position: Vec3) {
+    transform.translation = position;
+
+    animator.set_tweenable(Tween::new(
+        EaseFunction::QuadraticInOut,
+        Duration::ZERO,
+        TransformPositionLens {
+            start: position,
+            end: position,
+        },
+    ));
+}
+
+/// Whether the textures of the spawning planet or wave are loaded. A texture that failed
+/// to load doesn't hold up the run, its sprite is left blank.
+fn is_loading_done(loading: &AssetsLoading, asset_server: &AssetServer) -> bool {
+    match asset_server.get_group_load_state(loading.0.iter().map(|handle| handle.id())) {
+        LoadState::Loaded => true,
+        LoadState::Failed => {
+            warn!("Some of the planet textures failed to load");
+            true
+        }
+        _ => false,
+    }
+}
+
 fn check_planets_loading(
     mut next_loading_state: ResMut<NextState<LoadingState>>,
     mut loading: ResMut<AssetsLoading>,
     asset_server: Res<AssetServer>,
 ) {
-    if asset_server.get_group_load_state(loading.0.iter().map(|handle| handle.id()))
-        == LoadState::Loaded
-    {
-        // all assets are now ready
-        next_loading_state.set(LoadingState::Obstacles);
+    if is_loading_done(&loading, &asset_server) {
+        // all assets are now ready, the obstacles were spawned with the planet
+        next_loading_state.set(LoadingState::None);
 
         loading.0.clear();
 
-        println!("Planet has spawned!");
+        info!("Planet has spawned!");
     }
 }
 
@@ -448,118 +1240,486 @@ fn check_obstacles_loading(
     mut loading: ResMut<AssetsLoading>,
     asset_server: Res<AssetServer>,
 ) {
-    if asset_server.get_group_load_state(loading.0.iter().map(|handle| handle.id()))
-        == LoadState::Loaded
-    {
+    if is_loading_done(&loading, &asset_server) {
         // all assets are now ready
         next_loading_state.set(LoadingState::None);
 
         loading.0.clear();
 
-        println!("Obstacles has spawned!");
+        info!("Obstacles has spawned!");
     }
 }
 
-fn rotate_planets(mut planets_query: Query<(&mut Transform, &Planet)>, time: Res<Time>) {
-    for (mut planet_transform, planet_struct) in planets_query.iter_mut() {
-        if !planet_struct.is_playing {
+// The rotation eases in after the landing, instead of starting at full speed,
+// and eases out once the planet is cleared.
+fn rotate_planets(
+    mut planets_query: Query<(&mut Transform, &mut Planet)>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    run_modifiers: Res<RunModifiers>,
+    game_time: Res<GameTime>,
+) {
+    let rotation_speed = planet_rotation_speed(&game_balance, &settings, &run_modifiers);
+
+    for (mut planet_transform, mut planet_struct) in planets_query.iter_mut() {
+        let ease_step = game_time.delta_seconds() / PLANET_ROTATION_EASE_DURATION;
+        planet_struct.rotation_ease = if planet_struct.is_playing {
+            (planet_struct.rotation_ease + ease_step).min(1.)
+        } else {
+            (planet_struct.rotation_ease - ease_step).max(0.)
+        };
+
+        planet_transform
+            .rotate_z(rotation_speed * planet_struct.rotation_factor() * game_time.delta_seconds());
+    }
+}
+
+// Shrinks the cleared planets along with their rotation, despawning them once it stopped.
+fn retire_cleared_planets(
+    mut commands: Commands,
+    mut planet_query: Query<(Entity, &mut Transform, &Planet), With<ClearedPlanet>>,
+) {
+    for (planet_entity, mut planet_transform, planet_struct) in planet_query.iter_mut() {
+        if planet_struct.rotation_ease <= 0. {
+            despawn_planet(&mut commands, planet_entity, planet_struct);
             continue;
         }
 
-        planet_transform.rotate_z(-PLANET_ROTATION_SPEED * time.delta_seconds());
+        planet_transform.scale = Vec3::splat(planet_struct.rotation_factor());
+    }
+}
+
+/// Radians per second the active planet turns, clockwise or counter-clockwise in the mirrored level.
+/// The reversed rotation modifier flips it once more.
+fn planet_rotation_speed(
+    game_balance: &GameBalance,
+    settings: &GameSettings,
+    run_modifiers: &RunModifiers,
+) -> f32 {
+    if settings.mirror_level != run_modifiers.contains(RunModifier::ReversedRotation) {
+        game_balance.planet_rotation_speed
+    } else {
+        -game_balance.planet_rotation_speed
     }
 }
 
 // TODO: current
+#[allow(clippy::too_many_arguments)]
 fn shrink_current_planet(
     mut commands: Commands,
     mut planets_query: Query<(&mut Sprite, Entity, &mut Collider, &Transform, &mut Planet)>,
     mut planet_spawn_event_writer: EventWriter<PlanetSpawnEvent>,
     mut next_loading_state: ResMut<NextState<LoadingState>>,
+    mut score_event_writer: EventWriter<ScoreEvent>,
+    mut planet_cleared_event_writer: EventWriter<PlanetClearedEvent>,
+    mut next_app_state: ResMut<NextState<AppState>>,
     mut game_manager: ResMut<GameManager>,
-    time: Res<Time>,
+    run_config: Res<RunConfig>,
+    settings: Res<GameSettings>,
+    planet_definitions: Res<PlanetDefinitions>,
+    mut current_planet: ResMut<CurrentPlanet>,
+    planet_shrink_rate: Res<PlanetShrinkRate>,
+    game_balance: Res<GameBalance>,
+    run_modifiers: Res<RunModifiers>,
+    game_time: Res<GameTime>,
 ) {
+    let shrink_limit = settings.difficulty.shrink_limit();
+    let shrink_speed = game_balance.planet_shrink_speed * planet_shrink_rate.0;
+
     for (mut planet_sprite, planet_entity, mut collider, transform, mut planet_struct) in
         planets_query.iter_mut()
     {
+        // Set only on contact, so a planet never shrinks before the player lands on it.
         if !planet_struct.is_playing {
             continue;
         }
 
         let new_planet_size =
-            planet_sprite.custom_size.unwrap() - PLANET_SHRINK_SPEED * time.delta_seconds();
+            planet_sprite.custom_size.unwrap() - shrink_speed * game_time.delta_seconds();
 
-        collider.shape.radius -= PLANET_SHRINK_SPEED / 2.0 * time.delta_seconds();
+        collider.shape.radius -= shrink_speed / 2.0 * game_time.delta_seconds();
 
         planet_struct.radius = collider.shape.radius;
+        current_planet.radius = planet_struct.radius;
 
         planet_sprite.custom_size = Some(new_planet_size);
 
-        if new_planet_size.distance(PLANET_SHRINK_LIMIT) < 1. {
-            // When despawning this entity, other sprites are also despawning for some fucking weird reason.
-            for &obstacle_entity in planet_struct.obstacles.iter() {
-                commands.entity(obstacle_entity).despawn_recursive();
+        // Every wave but the last ends at an even step between the full size and the shrink limit.
+        let planet_def = planet_definitions.get(planet_struct.variant);
+        let waves = planet_def.waves.len();
+        let is_next_wave = planet_struct.wave + 1 < waves;
+        let planet_shrink_limit = if is_next_wave {
+            let waves_left = (waves - planet_struct.wave - 1) as f32;
+            let full_size = run_modifiers.planet_size(planet_def);
+            shrink_limit + (full_size - shrink_limit) * waves_left / waves as f32
+        } else {
+            shrink_limit
+        };
+
+        if is_next_wave && new_planet_size.y - planet_shrink_limit.y < 1. {
+            despawn_obstacles(&mut commands, &planet_struct.obstacles);
+            planet_struct.wave_obstacles_dodged += planet_struct.obstacles.len();
+            planet_struct.obstacles.clear();
+            planet_struct.wave += 1;
+
+            // Spawns the next pattern on the same planet.
+            next_loading_state.set(LoadingState::Obstacles);
+        } else if new_planet_size.y - planet_shrink_limit.y < 1. {
+            // The obstacles go right away, the planet eases out first.
+            despawn_obstacles(&mut commands, &planet_struct.obstacles);
+            planet_struct.is_playing = false;
+            commands.entity(planet_entity).insert(ClearedPlanet);
+            current_planet.entity = None;
+
+            game_manager.planets_cleared += 1;
+            planet_cleared_event_writer.send(PlanetClearedEvent {
+                variant: planet_struct.variant,
+                obstacles: planet_struct.wave_obstacles_dodged + planet_struct.obstacles.len(),
+            });
+            score_event_writer.send(ScoreEvent {
+                points: 1,
+                reason: ScoreReason::PlanetCleared,
+            });
+
+            let is_target_reached = run_config
+                .target_planets
+                .is_some_and(|target_planets| game_manager.planets_cleared >= target_planets);
+            let is_story_cleared = !game_manager.infinite_mode
+                && planet_struct.variant == PlanetVariant::Boss
+                && run_config.victory_after_story;
+
+            if is_target_reached || is_story_cleared {
+                info!("Run completed!");
+                next_app_state.set(AppState::Victory);
+                continue;
             }
-            commands.entity(planet_entity).despawn_recursive();
 
             next_loading_state.set(LoadingState::Planet);
 
+            let next_variant = planet_struct.variant.next(game_manager.infinite_mode);
+
             planet_spawn_event_writer.send(PlanetSpawnEvent {
-                planet_variant_to_spawn: planet_struct.variant.next(),
+                planet_variant_to_spawn: next_variant,
                 last_planet_position: transform.translation,
             });
 
-            if planet_struct.variant.next() == PlanetVariant::Earth {
+            if next_variant == PlanetVariant::Earth {
                 game_manager.infinite_mode = true;
             }
-
-            game_manager.score += 1;
         }
     }
 }
 
-fn manage_planet_face(
-    planet_query: Query<&Planet>,
-    mut planet_face_query: Query<(&mut PlanetFace, &mut TextureAtlasSprite)>,
-    time: Res<Time>,
+// Dims the planets the player hasn't landed on yet and outlines the shrinking one.
+fn highlight_active_planet(
+    mut planet_query: Query<(&Transform, &mut Sprite, &Planet)>,
+    mut gizmos: Gizmos,
 ) {
-    if let Ok(planet_struct) = planet_query.get_single() {
-        if let Ok((mut planet_face, mut face_atlas)) = planet_face_query.get_single_mut() {
-            if !planet_struct.is_playing {
-                return;
-            }
+    for (planet_transform, mut planet_sprite, planet_struct) in planet_query.iter_mut() {
+        let planet_color = if planet_struct.is_playing {
+            Color::WHITE
+        } else {
+            INACTIVE_PLANET_COLOR
+        };
 
-            if planet_struct.radius < PLANET_FACE_NORMAL_THRESHOLD {
-                face_atlas.index = 1;
-                planet_face.face = PlanetFaceState::Normal;
-            }
-            if planet_struct.radius < PLANET_FACE_BAD_THRESHOLD {
-                face_atlas.index = 2;
-                planet_face.face = PlanetFaceState::Bad;
-            }
+        if planet_sprite.color != planet_color {
+            planet_sprite.color = planet_color;
+        }
 
-            face_atlas.custom_size =
-                Some(face_atlas.custom_size.unwrap() - PLANET_SHRINK_SPEED * time.delta_seconds());
+        if planet_struct.is_playing {
+            gizmos.circle_2d(
+                planet_transform.translation.truncate(),
+                planet_struct.radius,
+                ACTIVE_PLANET_OUTLINE_COLOR,
+            );
         }
     }
 }
 
-fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let collider_shape = Ball::new(PLAYER_SIZE.y / 2. - 4.);
+// Draws the orbit ahead of every obstacle on the current planet, with the trajectory setting.
+fn draw_obstacle_trajectories(
+    mut gizmos: Gizmos,
+    obstacle_query: Query<&Obstacle>,
+    planet_query: Query<(&Transform, &Planet)>,
+    current_planet: Res<CurrentPlanet>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    run_modifiers: Res<RunModifiers>,
+) {
+    if !settings.show_obstacle_trajectories {
+        return;
+    }
 
-    commands.spawn((
-        SpriteBundle {
-            texture: asset_server.load("art/Piggy.png"),
-            sprite: Sprite {
-                custom_size: Some(PLAYER_SIZE),
-                ..default()
-            },
+    let Some((planet_transform, planet_struct)) = current_planet
+        .entity
+        .and_then(|planet_entity| planet_query.get(planet_entity).ok())
+    else {
+        return;
+    };
+
+    let center = planet_transform.translation.truncate();
+    let orbit_radius = planet_struct.radius + OBSTACLE_SIZE.y / 2.;
+
+    for obstacle_struct in obstacle_query.iter_many(&planet_struct.obstacles) {
+        let arc_angle = obstacle_angular_velocity(
+            obstacle_struct,
+            planet_struct,
+            &game_balance,
+            &settings,
+            &run_modifiers,
+        ) * OBSTACLE_TRAJECTORY_TIME;
+
+        let points = (0..=OBSTACLE_TRAJECTORY_SEGMENTS).map(|segment| {
+            let angle = obstacle_struct.angle
+                + arc_angle * segment as f32 / OBSTACLE_TRAJECTORY_SEGMENTS as f32;
+            center + Vec2::from_angle(angle) * orbit_radius
+        });
+
+        gizmos.linestrip_2d(points, OBSTACLE_TRAJECTORY_COLOR);
+    }
+}
+
+/// Moves a face radius threshold, so it keeps its place between the full planet radius
+/// and the shrink limit radius.
+fn scale_face_threshold(threshold: f32, shrink_limit: Vec2) -> f32 {
+    let full_radius = PLANET_SIZE.y / 2.;
+    let default_limit_radius = PLANET_SHRINK_LIMIT.y / 2.;
+    let limit_radius = shrink_limit.y / 2.;
+
+    limit_radius
+        + (threshold - default_limit_radius) * (full_radius - limit_radius)
+            / (full_radius - default_limit_radius)
+}
+
+/// Face of a planet with the `radius` and its index in the face atlas. The thresholds
+/// are scaled for the `shrink_limit`, a radius right at a threshold already shows the next face.
+fn face_state_for_radius(radius: f32, shrink_limit: Vec2) -> (usize, PlanetFaceState) {
+    if radius <= scale_face_threshold(PLANET_FACE_BAD_THRESHOLD, shrink_limit) {
+        (2, PlanetFaceState::Bad)
+    } else if radius <= scale_face_threshold(PLANET_FACE_NORMAL_THRESHOLD, shrink_limit) {
+        (1, PlanetFaceState::Normal)
+    } else {
+        (0, PlanetFaceState::Good)
+    }
+}
+
+/// How far the next face has faded in, from 0 at `PLANET_FACE_BLEND_RANGE` above the threshold
+/// to 1 at the threshold.
+fn face_blend(radius: f32, threshold: f32) -> f32 {
+    1. - ((radius - threshold) / PLANET_FACE_BLEND_RANGE).clamp(0., 1.)
+}
+
+fn manage_planet_face(
+    mut planet_face_query: Query<(&Parent, &mut PlanetFace, &mut TextureAtlasSprite)>,
+    mut face_overlay_query: Query<
+        (&Parent, &mut TextureAtlasSprite),
+        (With<PlanetFaceOverlay>, Without<PlanetFace>),
+    >,
+    current_planet: Res<CurrentPlanet>,
+    planet_shrink_rate: Res<PlanetShrinkRate>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    game_time: Res<GameTime>,
+) {
+    let Some(planet_entity) = current_planet.entity else {
+        return;
+    };
+
+    let Some((_, mut planet_face, mut face_atlas)) = planet_face_query
+        .iter_mut()
+        .find(|(parent, _, _)| parent.get() == planet_entity)
+    else {
+        return;
+    };
+
+    let shrink_limit = settings.difficulty.shrink_limit();
+    let normal_threshold = scale_face_threshold(PLANET_FACE_NORMAL_THRESHOLD, shrink_limit);
+    let bad_threshold = scale_face_threshold(PLANET_FACE_BAD_THRESHOLD, shrink_limit);
+
+    (face_atlas.index, planet_face.face) =
+        face_state_for_radius(current_planet.radius, shrink_limit);
+
+    let overlay_alpha = match planet_face.face {
+        PlanetFaceState::Good => face_blend(current_planet.radius, normal_threshold),
+        PlanetFaceState::Normal => face_blend(current_planet.radius, bad_threshold),
+        PlanetFaceState::Bad => 0.,
+    };
+
+    face_atlas.custom_size = Some(
+        face_atlas.custom_size.unwrap()
+            - game_balance.planet_shrink_speed * planet_shrink_rate.0 * game_time.delta_seconds(),
+    );
+
+    if let Some((_, mut overlay_atlas)) = face_overlay_query
+        .iter_mut()
+        .find(|(parent, _)| parent.get() == planet_entity)
+    {
+        overlay_atlas.index = (face_atlas.index + 1).min(2);
+        overlay_atlas.color.set_a(overlay_alpha);
+        overlay_atlas.custom_size = face_atlas.custom_size;
+    }
+}
+
+// Slows the shrink down the higher the airborne player is above the current planet,
+// by up to `AIRBORNE_SHRINK_SLOWDOWN` at the peak of a jump.
+fn update_planet_shrink_rate(
+    mut planet_shrink_rate: ResMut<PlanetShrinkRate>,
+    player_query: Query<(&Transform, &Player)>,
+    planet_query: Query<&Transform, With<Planet>>,
+    current_planet: Res<CurrentPlanet>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+) {
+    planet_shrink_rate.0 = 1.;
+
+    if !settings.airborne_shrink_slowdown {
+        return;
+    }
+
+    let (Ok((player_transform, player_struct)), Some(planet_transform)) = (
+        player_query.get_single(),
+        current_planet
+            .entity
+            .and_then(|planet_entity| planet_query.get(planet_entity).ok()),
+    ) else {
+        return;
+    };
+
+    if player_struct.is_grounded {
+        return;
+    }
+
+    let height = player_transform
+        .translation
+        .truncate()
+        .distance(planet_transform.translation.truncate())
+        - current_planet.radius
+        - PLAYER_SIZE.y / 2.;
+    let jump_progress = (height
+        / jump_peak_height(
+            &game_balance,
+            settings.gravity_scale,
+            settings.jump_strength_scale,
+        ))
+    .clamp(0., 1.);
+
+    planet_shrink_rate.0 = 1. - AIRBORNE_SHRINK_SLOWDOWN * jump_progress;
+}
+
+// Shifts the view up while the player jumps above the top edge, if the camera follow setting is on.
+fn camera_follow(
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    player_query: Query<(&Transform, &Player)>,
+    mut camera_follow: ResMut<CameraFollow>,
+    settings: Res<GameSettings>,
+) {
+    let mut offset = 0.;
+
+    if let (Ok((camera_transform, camera_projection)), Ok((player_transform, player_struct))) =
+        (camera_query.get_single(), player_query.get_single())
+    {
+        if settings.camera_follow && !player_struct.is_grounded {
+            let view_height = camera_projection.area.height();
+            let player_height = player_transform.translation.y - camera_transform.translation.y;
+            let overshoot = player_height + CAMERA_FOLLOW_MARGIN - view_height / 2.;
+
+            offset = (overshoot / view_height).clamp(0., CAMERA_FOLLOW_MAX_OFFSET);
+        }
+    }
+
+    camera_follow.offset = offset;
+}
+
+// Turns the camera, so the player on the planet appears upright and the world turns
+// around them. Only the rotation changes, the position tweens keep working.
+// Between planets it turns back to normal.
+fn rotate_camera(
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    player_query: Query<&Transform, (With<Player>, Without<Camera>)>,
+    planet_query: Query<&Transform, (With<Planet>, Without<Camera>)>,
+    current_planet: Res<CurrentPlanet>,
+    settings: Res<GameSettings>,
+    game_time: Res<GameTime>,
+) {
+    let Ok(mut camera_transform) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let player_planet = current_planet
+        .entity
+        .and_then(|planet_entity| planet_query.get(planet_entity).ok());
+
+    let target_rotation = match (
+        settings.rotate_camera,
+        player_query.get_single(),
+        player_planet,
+    ) {
+        (true, Ok(player_transform), Some(planet_transform)) => {
+            let offset = (player_transform.translation - planet_transform.translation).truncate();
+            Quat::from_rotation_z(offset.y.atan2(offset.x) - FRAC_PI_2)
+        }
+        _ => Quat::IDENTITY,
+    };
+
+    let smoothing = (CAMERA_ROTATION_SMOOTHING * game_time.delta_seconds()).min(1.);
+    camera_transform.rotation = camera_transform.rotation.slerp(target_rotation, smoothing);
+}
+
+/// While the player falls towards the next planet, shifts the view down to reveal its surface,
+/// and blends in the `CameraFollow` offset.
+/// Moves the projection origin rather than the transform, so it doesn't fight the camera `Animator`.
+fn camera_look_ahead(
+    mut camera_query: Query<&mut OrthographicProjection, With<Camera>>,
+    player_query: Query<&Player>,
+    current_planet: Res<CurrentPlanet>,
+    camera_follow: Res<CameraFollow>,
+    game_time: Res<GameTime>,
+) {
+    if let Ok(mut camera_projection) = camera_query.get_single_mut() {
+        let is_falling = player_query
+            .get_single()
+            .map_or(false, |player| !player.is_grounded && player.velocity < 0.);
+        let is_between_planets = current_planet.entity.is_none();
+
+        let look_ahead = if is_falling && is_between_planets {
+            CAMERA_LOOK_AHEAD
+        } else {
+            0.
+        };
+        let target_origin_y = 0.5 + look_ahead - camera_follow.offset;
+
+        let smoothing = (CAMERA_LOOK_AHEAD_SMOOTHING * game_time.delta_seconds()).min(1.);
+        camera_projection.viewport_origin.y +=
+            (target_origin_y - camera_projection.viewport_origin.y) * smoothing;
+    }
+}
+
+fn spawn_player(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+) {
+    let collider_shape = Ball::new(PLAYER_SIZE.y / 2. - PLAYER_COLLIDER_INSET);
+
+    // Dash is ready from the start.
+    let mut dash_cooldown = Timer::from_seconds(game_balance.player_dash_cooldown, TimerMode::Once);
+    dash_cooldown.tick(dash_cooldown.duration());
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(settings.player_skin.texture_path()),
+            sprite: Sprite {
+                custom_size: Some(PLAYER_SIZE),
+                ..default()
+            },
             transform: Transform::from_translation(PLAYER_START_POSITION),
             ..default()
         },
         Player {
             is_grounded: false,
             velocity: 0.,
+            dash_cooldown,
         },
         Collider {
             shape: collider_shape,
@@ -567,50 +1727,233 @@ fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
+#[allow(clippy::too_many_arguments)]
 fn player_jump(
     mut player_query: Query<(&mut Transform, &mut Player)>,
+    mut player_jumped_event_writer: EventWriter<PlayerJumpedEvent>,
     mut commands: Commands,
+    planet_query: Query<(), With<Planet>>,
     keyboard_input: Res<Input<KeyCode>>,
-    time: Res<Time>,
-    asset_server: Res<AssetServer>,
+    game_time: Res<GameTime>,
+    game_assets: Res<GameAssets>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    run_modifiers: Res<RunModifiers>,
 ) {
+    let key_bindings = &settings.key_bindings;
+
     if let Ok((mut player_transform, mut player_struct)) = player_query.get_single_mut() {
+        // The player waits at the start, until the first planet of the run is spawned.
+        if planet_query.is_empty() {
+            player_struct.velocity = 0.;
+            return;
+        }
+
         if player_struct.is_grounded {
             player_struct.velocity = 0.;
         }
 
-        player_struct.velocity += GRAVITY_STRENGTH * GRAVITY_STRENGTH.abs() * time.delta_seconds();
+        player_struct.velocity +=
+            game_balance.gravity_strength * settings.gravity_scale * game_time.delta_seconds();
+
+        // Letting go early ends the rise sooner. Auto-jump hops don't hold the key at all.
+        if settings.variable_jump
+            && !settings.auto_jump
+            && !player_struct.is_grounded
+            && player_struct.velocity > 0.
+            && keyboard_input.just_released(key_bindings.jump)
+        {
+            player_struct.velocity *= game_balance.player_jump_cut;
+        }
 
-        if keyboard_input.just_pressed(KeyCode::Space) && player_struct.is_grounded {
-            player_struct.velocity = PLAYER_JUMP_STRENGTH;
+        // Auto-jump assist hops on every landing, unless the jump key holds the player down.
+        let wants_jump = if settings.auto_jump {
+            !keyboard_input.pressed(key_bindings.jump)
+        } else {
+            keyboard_input.just_pressed(key_bindings.jump)
+        };
 
-            // Play jump sound
-            commands.spawn(AudioBundle {
-                source: asset_server.load("sounds/350905__cabled_mess__jump_c_05.ogg"),
-                settings: PlaybackSettings {
-                    mode: bevy::audio::PlaybackMode::Despawn,
-                    ..default()
-                },
-                ..default()
+        if wants_jump && player_struct.is_grounded {
+            player_struct.velocity =
+                game_balance.player_jump_strength * settings.jump_strength_scale;
+            player_jumped_event_writer.send(PlayerJumpedEvent {
+                position: player_transform.translation,
             });
+
+            // Play one of the jump sounds with a slightly random pitch
+            let rng = &mut rand::thread_rng();
+            let speed =
+                1. + rng.gen_range(-JUMP_SOUND_PITCH_VARIATION..=JUMP_SOUND_PITCH_VARIATION);
+            if let Some(jump_sound) = game_assets.jump_sounds.choose(rng) {
+                play_sound_effect(&mut commands, jump_sound.clone(), &settings, speed);
+            }
+        }
+
+        // accelerate fall, or rise with the inverted setting
+        if keyboard_input.pressed(key_bindings.fast_fall)
+            && !player_struct.is_grounded
+            && !run_modifiers.contains(RunModifier::NoFastFall)
+        {
+            if settings.invert_fast_fall {
+                player_struct.velocity -=
+                    game_balance.player_fall_acceleration * game_time.delta_seconds();
+                player_struct.velocity = player_struct
+                    .velocity
+                    .min(game_balance.player_max_rise_velocity);
+            } else {
+                player_struct.velocity +=
+                    game_balance.player_fall_acceleration * game_time.delta_seconds();
+            }
         }
 
-        // accelerate fall
-        if keyboard_input.pressed(KeyCode::S) && !player_struct.is_grounded {
-            player_struct.velocity += PLAYER_FALL_ACCELERATION * time.delta_seconds();
+        player_transform.translation.y += player_struct.velocity * game_time.delta_seconds();
+    }
+}
+
+/// Moves the grounded player around the active planet, in the same direction as the obstacles.
+fn player_dash(
+    mut player_query: Query<(&mut Transform, &mut Player), Without<Planet>>,
+    planet_query: Query<&Transform, With<Planet>>,
+    current_planet: Res<CurrentPlanet>,
+    keyboard_input: Res<Input<KeyCode>>,
+    game_time: Res<GameTime>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+) {
+    let Ok((mut player_transform, mut player_struct)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    player_struct.dash_cooldown.tick(game_time.delta());
+
+    if !keyboard_input.just_pressed(settings.key_bindings.dash)
+        || !player_struct.is_grounded
+        || !player_struct.dash_cooldown.finished()
+    {
+        return;
+    }
+
+    let Some(Ok(planet_transform)) = current_planet
+        .entity
+        .map(|planet_entity| planet_query.get(planet_entity))
+    else {
+        return;
+    };
+
+    let offset = (player_transform.translation - planet_transform.translation).truncate();
+    let dash_angle = if settings.mirror_level {
+        -game_balance.player_dash_angle
+    } else {
+        game_balance.player_dash_angle
+    };
+    let player_angle = offset.y.atan2(offset.x) - dash_angle;
+
+    player_transform.translation.x =
+        planet_transform.translation.x + player_angle.cos() * offset.length();
+    player_transform.translation.y =
+        planet_transform.translation.y + player_angle.sin() * offset.length();
+
+    player_struct.dash_cooldown.reset();
+}
+
+fn apply_score_events(
+    mut score_event_reader: EventReader<ScoreEvent>,
+    mut game_manager: ResMut<GameManager>,
+    run_modifiers: Res<RunModifiers>,
+) {
+    for score_event in score_event_reader.iter() {
+        let points = score_event.points * run_modifiers.score_multiplier();
+        game_manager.score += points;
+
+        if matches!(score_event.reason, ScoreReason::NearMiss) {
+            game_manager.bonus_score += points;
         }
+    }
+}
+
+/// Plays a one-shot sound at the SFX volume from the settings.
+fn play_sound_effect(
+    commands: &mut Commands,
+    source: Handle<AudioSource>,
+    settings: &GameSettings,
+    speed: f32,
+) {
+    commands.spawn(AudioBundle {
+        source,
+        settings: PlaybackSettings {
+            mode: bevy::audio::PlaybackMode::Despawn,
+            volume: Volume::Relative(VolumeLevel::new(settings.sfx_volume)),
+            speed,
+            ..default()
+        },
+    });
+}
+
+fn apply_music_volume(music_query: Query<&AudioSink, With<Music>>, settings: Res<GameSettings>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for music_sink in music_query.iter() {
+        music_sink.set_volume(MUSIC_BASE_VOLUME * settings.music_volume);
+    }
+}
+
+/// Measures how fast the player reaches each next planet, and breaks the combo if too slow.
+#[allow(clippy::too_many_arguments)]
+fn track_combo(
+    mut commands: Commands,
+    mut planet_spawn_event_reader: EventReader<PlanetSpawnEvent>,
+    mut planet_activated_event_reader: EventReader<PlanetActivatedEvent>,
+    mut combo_broken_event_writer: EventWriter<ComboBrokenEvent>,
+    mut combo: ResMut<Combo>,
+    mut descent_time: Local<Option<f32>>,
+    game_manager: Res<GameManager>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    game_assets: Res<GameAssets>,
+    game_time: Res<GameTime>,
+) {
+    if let Some(descent_time) = descent_time.as_mut() {
+        *descent_time += game_time.delta_seconds();
+    }
+
+    for _ in planet_spawn_event_reader.iter() {
+        // The first planet of the run doesn't count.
+        *descent_time = if game_manager.planets_cleared > 0 {
+            Some(0.)
+        } else {
+            None
+        };
+    }
 
-        player_transform.translation.y += player_struct.velocity * time.delta_seconds();
+    for _ in planet_activated_event_reader.iter() {
+        if let Some(time_to_land) = descent_time.take() {
+            if time_to_land <= game_balance.combo_landing_time {
+                combo.count += 1;
+            } else if combo.count > 0 {
+                combo_broken_event_writer.send(ComboBrokenEvent {
+                    lost_combo: combo.count,
+                });
+                combo.count = 0;
+
+                // Slowed down jump sound as the combo break sound.
+                if let Some(jump_sound) = game_assets.jump_sounds.first() {
+                    play_sound_effect(&mut commands, jump_sound.clone(), &settings, 0.5);
+                }
+            }
+        }
     }
 }
 
-/// When pressing G - renders all gizmos.
+/// When pressing G or with `always_show_colliders` setting - renders all gizmos.
 pub fn show_gizmos(
     mut gizmos: Gizmos,
     collider_query: Query<(&Transform, &Collider)>,
     keyboard: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
 ) {
-    if keyboard.pressed(KeyCode::G) {
+    if keyboard.pressed(KeyCode::G) || settings.always_show_colliders {
         for (transform, collider) in collider_query.iter() {
             let collider_position = transform.translation;
             gizmos.circle_2d(
@@ -622,37 +1965,63 @@ pub fn show_gizmos(
     }
 }
 
+/// Computes the contact between two colliders, if they are closer than `distance`.
+fn collider_contact(
+    first_transform: &Transform,
+    first_collider: &Collider,
+    second_transform: &Transform,
+    second_collider: &Collider,
+    distance: f32,
+) -> Option<Contact> {
+    let first_isometry =
+        Isometry::translation(first_transform.translation.x, first_transform.translation.y);
+    let second_isometry = Isometry::translation(
+        second_transform.translation.x,
+        second_transform.translation.y,
+    );
+
+    contact(
+        &first_isometry,
+        &first_collider.shape,
+        &second_isometry,
+        &second_collider.shape,
+        distance,
+    )
+    .unwrap()
+}
+
+// Halfway between the touching points of both shapes.
+fn contact_point(contact: &Contact) -> Vec2 {
+    Vec2::new(
+        contact.point1.x + contact.point2.x,
+        contact.point1.y + contact.point2.y,
+    ) / 2.
+}
+
 fn check_player_planet_collisions(
     mut player_query: Query<(&Collider, &mut Transform, &mut Player), Without<Planet>>,
-    mut planet_query: Query<(&Collider, &Transform, &mut Planet)>,
+    mut planet_query: Query<(Entity, &Collider, &Transform, &mut Planet), Without<ClearedPlanet>>,
+    mut planet_activated_event_writer: EventWriter<PlanetActivatedEvent>,
+    mut player_landed_event_writer: EventWriter<PlayerLandedEvent>,
+    mut current_planet: ResMut<CurrentPlanet>,
 ) {
     for (player_collider, mut player_transform, mut player_struct) in player_query.iter_mut() {
-        for (planet_collider, planet_transform, mut planet_struct) in planet_query.iter_mut() {
+        let was_grounded = player_struct.is_grounded;
+        let mut landed_planet = None;
+
+        for (planet_entity, planet_collider, planet_transform, mut planet_struct) in
+            planet_query.iter_mut()
+        {
             let mut player_translation = player_transform.translation;
 
-            let actor_isometry = Isometry::translation(
-                player_transform.translation.x,
-                player_transform.translation.y,
-            );
-            let tile_isometry = Isometry::translation(
-                planet_transform.translation.x,
-                planet_transform.translation.y,
+            let collision = collider_contact(
+                &player_transform,
+                player_collider,
+                planet_transform,
+                planet_collider,
+                PLANET_CONTACT_MARGIN,
             );
 
-            let actor_shape = player_collider.shape.clone_box();
-            let tile_shape = planet_collider.shape.clone_box();
-
-            // Distance between objects to collide
-            let distance = 1.;
-            let collision = contact(
-                &actor_isometry,
-                &*actor_shape,
-                &tile_isometry,
-                &*tile_shape,
-                distance,
-            )
-            .unwrap();
-
             // If objects collided
             if let Some(contact) = collision {
                 let normal = contact.normal1.into_inner();
@@ -660,189 +2029,1207 @@ fn check_player_planet_collisions(
                 player_translation.x += contact.dist * normal.x;
                 player_translation.y += contact.dist * normal.y;
 
-                player_struct.is_grounded = true;
+                landed_planet = Some(planet_entity);
+
+                if !planet_struct.is_playing {
+                    planet_activated_event_writer.send(PlanetActivatedEvent {
+                        planet: planet_entity,
+                        variant: planet_struct.variant,
+                    });
+
+                    *current_planet = CurrentPlanet {
+                        entity: Some(planet_entity),
+                        variant: planet_struct.variant,
+                        radius: planet_struct.radius,
+                    };
+                }
                 planet_struct.is_playing = true;
-            } else {
-                player_struct.is_grounded = false;
             }
 
             player_transform.translation = player_translation;
         }
+
+        // Standing on any of the planets keeps the player grounded.
+        player_struct.is_grounded = landed_planet.is_some();
+
+        if let (false, Some(planet_entity)) = (was_grounded, landed_planet) {
+            player_landed_event_writer.send(PlayerLandedEvent {
+                planet: planet_entity,
+                position: player_transform.translation,
+            });
+        }
     }
 }
 
 fn check_player_obstacle_collisions(
-    mut next_app_state: ResMut<NextState<AppState>>,
-    mut player_query: Query<(&Collider, &mut Transform), (With<Player>, Without<Obstacle>)>,
-    mut obstacle_query: Query<(&Collider, &Transform), With<Obstacle>>,
-) {
-    for (player_collider, player_transform) in player_query.iter_mut() {
-        for (obstacle_collider, obstacle_transform) in obstacle_query.iter_mut() {
-            let actor_isometry = Isometry::translation(
-                player_transform.translation.x,
-                player_transform.translation.y,
-            );
-            let tile_isometry = Isometry::translation(
-                obstacle_transform.translation.x,
-                obstacle_transform.translation.y,
-            );
+    mut player_hit_event_writer: EventWriter<PlayerHitEvent>,
+    mut player_query: Query<
+        (&Collider, &mut Transform, &Player, Option<&Invincible>),
+        (With<Player>, Without<Obstacle>),
+    >,
+    mut obstacle_query: Query<
+        (Entity, &Collider, &Transform, &Obstacle),
+        (Without<ObstacleFadeIn>, Without<ObstacleDormant>),
+    >,
+    mut score_event_writer: EventWriter<ScoreEvent>,
+    mut near_obstacles: Local<HashSet<Entity>>,
+    game_balance: Res<GameBalance>,
+) {
+    for (player_collider, player_transform, player_struct, invincible) in player_query.iter_mut() {
+        for (obstacle_entity, obstacle_collider, obstacle_transform, obstacle_struct) in
+            obstacle_query.iter_mut()
+        {
+            // Decoys neither hit nor count as near misses.
+            if !obstacle_struct.lethal {
+                continue;
+            }
 
-            let actor_shape = player_collider.shape.clone_box();
-            let tile_shape = obstacle_collider.shape.clone_box();
-
-            // Distance between objects to collide
-            let distance = 0.0;
-            let collision = contact(
-                &actor_isometry,
-                &*actor_shape,
-                &tile_isometry,
-                &*tile_shape,
-                distance,
-            )
-            .unwrap();
+            let collision = collider_contact(
+                &player_transform,
+                player_collider,
+                obstacle_transform,
+                obstacle_collider,
+                OBSTACLE_CONTACT_MARGIN,
+            );
 
             // If objects collided
-            if let Some(_) = collision {
-                println!("Player has collided with obstacle!");
-                next_app_state.set(AppState::GameOver);
+            if let Some(contact) = collision {
+                // The player passes through while invincible.
+                if invincible.is_some() {
+                    near_obstacles.remove(&obstacle_entity);
+                    continue;
+                }
+
+                info!("Player has collided with obstacle!");
+                player_hit_event_writer.send(PlayerHitEvent {
+                    cause: DeathCause::ObstacleHit,
+                    contact_point: Some(contact_point(&contact)),
+                });
+                continue;
+            }
+
+            let near_miss = collider_contact(
+                &player_transform,
+                player_collider,
+                obstacle_transform,
+                obstacle_collider,
+                game_balance.near_miss_margin,
+            );
+
+            // Counts once per pass, when the obstacle leaves the margin without hitting.
+            if near_miss.is_some() {
+                if !player_struct.is_grounded {
+                    near_obstacles.insert(obstacle_entity);
+                }
+            } else if near_obstacles.remove(&obstacle_entity) {
+                score_event_writer.send(ScoreEvent {
+                    points: NEAR_MISS_POINTS,
+                    reason: ScoreReason::NearMiss,
+                });
             }
         }
     }
+
+    // Forget the despawned obstacles.
+    near_obstacles.retain(|&obstacle_entity| obstacle_query.contains(obstacle_entity));
 }
 
-// When the new planet appears, it is filled with new obstacles.
-// TODO: SPRITES NOT THE SAME WITH THE PLAYER ARE LOADING TOO SLOW.
-fn spawn_obstacles(
+// Ends the run on a hit, unless the easy difficulty rewinds the player instead
+// or a spare life takes it. Obstacle hits freeze the gameplay for a moment first.
+#[allow(clippy::too_many_arguments)]
+fn handle_player_hit(
     mut commands: Commands,
-    mut planet_query: Query<(&Transform, &mut Planet)>,
-    mut loading: ResMut<AssetsLoading>,
-    game_manager: Res<GameManager>,
-    asset_server: Res<AssetServer>,
+    mut player_hit_event_reader: EventReader<PlayerHitEvent>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Sprite,
+            &mut Player,
+            Option<&Invincible>,
+        ),
+        Without<Planet>,
+    >,
+    planet_query: Query<(&Transform, &Planet)>,
+    mut state_history: ResMut<StateHistory>,
+    mut game_manager: ResMut<GameManager>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
 ) {
-    let texture = asset_server.load("art/Wolf.png");
-    println!(
-        "Num of planets when spawning obstacles: {}",
-        planet_query.iter().len()
-    );
+    let Some(&PlayerHitEvent {
+        cause,
+        contact_point,
+    }) = player_hit_event_reader.iter().last()
+    else {
+        return;
+    };
+
+    let Ok((player_entity, mut player_transform, mut player_sprite, mut player_struct, invincible)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    // Nothing hurts in freeplay, falling off puts the player on top of the lowest planet.
+    if settings.freeplay {
+        let lowest_planet = planet_query
+            .iter()
+            .min_by(|first, second| first.0.translation.y.total_cmp(&second.0.translation.y));
+
+        if let (DeathCause::FellIntoVoid, Some((planet_transform, planet_struct))) =
+            (cause, lowest_planet)
+        {
+            player_transform.translation.x = planet_transform.translation.x;
+            player_transform.translation.y =
+                planet_transform.translation.y + planet_struct.radius + PLAYER_SIZE.y / 2.;
+            player_struct.velocity = 0.;
+        }
+
+        return;
+    }
 
-    if let Ok((planet_transform, mut planet_struct)) = planet_query.get_single_mut() {
-        let mut rng = rand::thread_rng();
-        let mut obstacles_num = rng.gen_range(1..=OBSTACLES_MAX_NUM);
+    // Invincibility can't save from the void.
+    if invincible.is_some() && !matches!(cause, DeathCause::FellIntoVoid) {
+        return;
+    }
 
-        let mut last_obstacle_angle: f32 = 0.;
+    if rewind::rewind_player(
+        &mut commands,
+        player_entity,
+        &mut player_transform,
+        &mut player_struct,
+        &mut state_history,
+    ) {
+        return;
+    }
 
-        if !game_manager.infinite_mode {
-            obstacles_num = planet_struct.variant.get_obstacles().len();
+    // A spare life takes the hit, the void still ends the run.
+    if game_manager.lives > 1 && !matches!(cause, DeathCause::FellIntoVoid) {
+        game_manager.lives -= 1;
+
+        if let Some(contact_point) = contact_point {
+            spawn_particle_burst(
+                &mut commands,
+                contact_point.extend(player_transform.translation.z + 1.),
+                HIT_SPARK_COLOR,
+                HIT_SPARK_COUNT,
+                HIT_SPARK_SPEED,
+            );
         }
 
-        for i in 0..obstacles_num {
-            // Random position on the planet.
-            let mut obstacle_position = Vec3::ZERO;
-            let mut angle = if rng.gen_bool(0.5) {
-                rng.gen_range(0f32..=OBSTACLE_MIN_ANGLE_GENERATION)
-            } else {
-                rng.gen_range(OBSTACLE_MAX_ANGLE_GENERATION..=2. * PI)
-            };
+        commands.entity(player_entity).insert(Invincible {
+            timer: Timer::from_seconds(LIFE_LOST_INVINCIBILITY_DURATION, TimerMode::Once),
+        });
 
-            if last_obstacle_angle != 0. {
-                if (angle - last_obstacle_angle).abs() < OBSTACLE_CLOSE_GAP_RANGE.1 {
-                    angle -= rng.gen_range(OBSTACLE_CLOSE_GAP_RANGE.0..OBSTACLE_CLOSE_GAP_RANGE.1);
-                } else if (angle - last_obstacle_angle).abs() < OBSTACLE_LONG_GAP_RANGE.1 {
-                    angle -= rng.gen_range(OBSTACLE_LONG_GAP_RANGE.0..OBSTACLE_LONG_GAP_RANGE.1);
-                }
-            }
+        info!("Lost a life, {} left", game_manager.lives);
+        return;
+    }
 
-            // angle = angle.clamp(0., OBSTACLE_MAX_ANGLE_GENERATION);
+    game_manager.lives = 0;
+    commands.insert_resource(cause);
+
+    // Shows what got the player, before the game over screen covers it.
+    if let Some(contact_point) = contact_point {
+        spawn_particle_burst(
+            &mut commands,
+            contact_point.extend(player_transform.translation.z + 1.),
+            HIT_SPARK_COLOR,
+            HIT_SPARK_COUNT,
+            HIT_SPARK_SPEED,
+        );
+        player_sprite.color = PLAYER_HIT_COLOR;
+    }
 
-            println!(
-                "Last angle | New angle: {} , {}",
-                last_obstacle_angle, angle
-            );
+    match cause {
+        DeathCause::ObstacleHit => {
+            commands.insert_resource(HitStop(Timer::from_seconds(
+                game_balance.hit_stop_duration,
+                TimerMode::Once,
+            )));
+        }
+        DeathCause::ProjectileHit | DeathCause::FellIntoVoid | DeathCause::Forced => {
+            next_app_state.set(AppState::GameOver);
+        }
+    }
+}
 
-            last_obstacle_angle = angle;
+// Shakes the camera while the gameplay is frozen, then ends the run.
+fn tick_hit_stop(
+    mut commands: Commands,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    mut hit_stop: ResMut<HitStop>,
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+    mut camera_origin: Local<Option<Vec3>>,
+) {
+    let Ok(mut camera_transform) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let origin = *camera_origin.get_or_insert(camera_transform.translation);
+
+    if hit_stop.0.tick(gameplay_delta(&time)).finished() {
+        camera_transform.translation = origin;
+        *camera_origin = None;
+
+        commands.remove_resource::<HitStop>();
+        next_app_state.set(AppState::GameOver);
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let strength =
+        HIT_STOP_SHAKE_STRENGTH * settings.screen_shake_intensity * hit_stop.0.percent_left();
+    camera_transform.translation = origin
+        + Vec3::new(
+            rng.gen_range(-strength..=strength),
+            rng.gen_range(-strength..=strength),
+            0.,
+        );
+}
+
+/// Recognizable obstacle layouts of infinite mode, one is picked at random for every planet.
+#[derive(Clone, Copy, Debug)]
+enum ClusterPattern {
+    /// Spaced random angles, see `generate_obstacle_angles`.
+    Scattered,
+    /// Pairs of obstacles right next to each other, spread over the arc.
+    TightPairs,
+    /// Obstacles spread evenly over the arc.
+    EvenlySpaced,
+    /// Obstacles packed at one end of the arc, leaving one big gap.
+    BigGap,
+}
 
-            if !game_manager.infinite_mode {
-                angle = planet_struct.variant.get_obstacles()[i];
+impl ClusterPattern {
+    const ALL: [ClusterPattern; 4] = [
+        ClusterPattern::Scattered,
+        ClusterPattern::TightPairs,
+        ClusterPattern::EvenlySpaced,
+        ClusterPattern::BigGap,
+    ];
+
+    /// Up to `count` obstacle angles (in radians) of the pattern inside of the `arc`,
+    /// which never covers the landing spot.
+    fn angles(self, rng: &mut impl Rng, count: usize, arc: (f32, f32)) -> Vec<f32> {
+        let (arc_from, arc_to) = safe_obstacle_arc(arc);
+        let arc_length = arc_to - arc_from;
+
+        let angles: Vec<f32> = match self {
+            ClusterPattern::Scattered => return generate_obstacle_angles(rng, count, arc),
+            ClusterPattern::TightPairs => {
+                let pairs = (count + 1) / 2;
+                let section = arc_length / pairs as f32;
+
+                (0..pairs)
+                    .flat_map(|pair| {
+                        let center =
+                            arc_from + section * (pair as f32 + rng.gen_range(0.25..=0.75));
+                        [
+                            center - OBSTACLE_PAIR_GAP / 2.,
+                            center + OBSTACLE_PAIR_GAP / 2.,
+                        ]
+                    })
+                    .take(count)
+                    .collect()
             }
+            ClusterPattern::EvenlySpaced => {
+                let section = arc_length / count as f32;
 
-            let planet_radius = planet_struct.radius;
-            let obstacle_radius = OBSTACLE_SIZE.y / 2.;
-
-            obstacle_position.x =
-                planet_transform.translation.x + angle.cos() * (planet_radius + obstacle_radius);
-            obstacle_position.y =
-                planet_transform.translation.y + angle.sin() * (planet_radius + obstacle_radius);
-
-            planet_struct.obstacles.push(
-                commands
-                    .spawn((
-                        SpriteBundle {
-                            transform: Transform::from_translation(obstacle_position),
-                            texture: texture.clone(),
-                            sprite: Sprite {
-                                custom_size: Some(OBSTACLE_SIZE),
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Collider {
-                            shape: Ball::new(OBSTACLE_SIZE.y / 2. - 6.),
-                        },
-                        Obstacle { angle },
-                    ))
-                    .id(),
-            );
+                (0..count)
+                    .map(|index| arc_from + section * (index as f32 + 0.5))
+                    .collect()
+            }
+            ClusterPattern::BigGap => {
+                let from_start = rng.gen_bool(0.5);
+
+                (0..count)
+                    .map(|index| {
+                        let offset = OBSTACLE_PACKED_GAP * index as f32;
+                        if from_start {
+                            arc_from + offset
+                        } else {
+                            arc_to - offset
+                        }
+                    })
+                    .collect()
+            }
+        };
 
-            loading.0.push(texture.clone_untyped());
+        // Small arcs can't fit every obstacle, the overlapping ones are dropped.
+        let mut placed_angles: Vec<f32> = Vec::with_capacity(angles.len());
+        for angle in angles {
+            let angle = angle.clamp(arc_from, arc_to);
+            let overlaps = placed_angles
+                .iter()
+                .any(|&placed_angle| angle_distance(angle, placed_angle) < OBSTACLE_MIN_GAP);
+
+            if !overlaps {
+                placed_angles.push(angle);
+            }
         }
+
+        placed_angles
+            .into_iter()
+            .map(|angle| angle.rem_euclid(2. * PI))
+            .collect()
     }
 }
 
-fn move_obstacles_on_planet(
-    mut children_query: Query<(&mut Transform, &mut Obstacle)>,
-    planet_query: Query<(&Planet, &Transform), Without<Obstacle>>,
-    time: Res<Time>,
-) {
-    if let Ok((planet_struct, planet_transform)) = planet_query.get_single() {
-        if !planet_struct.is_playing {
-            return;
+/// Most random obstacles a planet of the `radius` gets, one per `OBSTACLE_SURFACE_SPACING`
+/// of its circumference. Big planets get more, but never over the `obstacles_max_num`.
+fn obstacles_max_num_for_radius(radius: f32, game_balance: &GameBalance) -> usize {
+    let surface_num = (2. * PI * radius / OBSTACLE_SURFACE_SPACING) as usize;
+
+    surface_num.clamp(1, game_balance.obstacles_max_num)
+}
+
+/// Turns the authored story angles by the `offset`. Obstacles the turn would move onto
+/// the landing spot are pushed out over the closer edge of the `STORY_LANDING_CLEAR_ARC`,
+/// in `OBSTACLE_MIN_GAP` steps until they don't overlap the other obstacles.
+fn shift_story_angles(angles: &[f32], offset: f32) -> Vec<f32> {
+    let (arc_from, arc_to) = STORY_LANDING_CLEAR_ARC;
+    let is_in_clear_arc = |angle: f32| (arc_from..=arc_to).contains(&angle.rem_euclid(2. * PI));
+
+    let mut shifted_angles: Vec<f32> = angles
+        .iter()
+        .map(|angle| (angle + offset).rem_euclid(2. * PI))
+        .collect();
+    // Authored obstacles on the landing spot stay there.
+    let mut is_placed: Vec<bool> = angles
+        .iter()
+        .zip(&shifted_angles)
+        .map(|(&angle, &shifted_angle)| !is_in_clear_arc(shifted_angle) || is_in_clear_arc(angle))
+        .collect();
+
+    let max_steps = (2. * PI / OBSTACLE_MIN_GAP) as usize;
+
+    for index in 0..angles.len() {
+        if is_placed[index] {
+            continue;
         }
 
-        let planet_translation = planet_transform.translation;
-        let planet_radius = planet_struct.radius;
+        let shifted_angle = shifted_angles[index];
+        let (edge, direction) = if shifted_angle - arc_from < arc_to - shifted_angle {
+            (arc_from, -1.)
+        } else {
+            (arc_to, 1.)
+        };
 
-        for &child in planet_struct.obstacles.iter() {
-            let child_query = children_query.get_mut(child);
+        let pushed_angle = (0..max_steps)
+            .map(|step| (edge + direction * step as f32 * OBSTACLE_MIN_GAP).rem_euclid(2. * PI))
+            .find(|&angle| {
+                shifted_angles
+                    .iter()
+                    .zip(&is_placed)
+                    .filter(|(_, &is_other_placed)| is_other_placed)
+                    .all(|(&other_angle, _)| angle_distance(angle, other_angle) >= OBSTACLE_MIN_GAP)
+            })
+            .unwrap_or(edge);
+
+        shifted_angles[index] = pushed_angle;
+        is_placed[index] = true;
+    }
 
-            if let Ok((mut transform, mut obstacle_struct)) = child_query {
-                let obstacle_radius = OBSTACLE_SIZE.y / 2.;
+    shifted_angles
+}
+
+/// Reflects the obstacle angle across the vertical axis of the planet.
+fn mirror_angle(angle: f32) -> f32 {
+    (PI - angle).rem_euclid(2. * PI)
+}
+
+/// The `arc` cut down to the `OBSTACLE_FULL_ARC`, so obstacles never cover the landing spot.
+fn safe_obstacle_arc(arc: (f32, f32)) -> (f32, f32) {
+    let arc_from = arc.0.max(OBSTACLE_FULL_ARC.0);
+    let arc_to = arc.1.min(OBSTACLE_FULL_ARC.1).max(arc_from);
+
+    (arc_from, arc_to)
+}
+
+/// Generates up to `count` random obstacle angles (in radians) for infinite mode inside of the `arc`.
+/// Consecutive obstacles are pushed apart by `OBSTACLE_CLOSE_GAP_RANGE` or
+/// `OBSTACLE_LONG_GAP_RANGE`, so the result only depends on the given `rng`.
+/// An angle closer than `OBSTACLE_MIN_GAP` to any placed obstacle is rerolled,
+/// and dropped after `OBSTACLE_PLACEMENT_ATTEMPTS` tries.
+fn generate_obstacle_angles(rng: &mut impl Rng, count: usize, arc: (f32, f32)) -> Vec<f32> {
+    // Never generate obstacles on the landing spot.
+    let (arc_from, arc_to) = safe_obstacle_arc(arc);
+
+    // Small arcs get fewer obstacles up front, instead of running out of attempts.
+    let count = count.min(max_obstacles_in_arc((arc_from, arc_to)));
 
-                transform.translation.x = planet_translation.x
-                    + obstacle_struct.angle.cos() * (planet_radius + obstacle_radius);
-                transform.translation.y = planet_translation.y
-                    + obstacle_struct.angle.sin() * (planet_radius + obstacle_radius);
+    let mut angles: Vec<f32> = Vec::with_capacity(count);
+    let mut last_obstacle_angle: Option<f32> = None;
 
-                obstacle_struct.angle -= time.delta_seconds() * OBSTACLE_MOVEMENT_SPEED;
+    for _ in 0..count {
+        let mut is_placed = false;
 
-                if obstacle_struct.angle.abs() > PI * 2. {
-                    obstacle_struct.angle = 0.;
+        for _ in 0..OBSTACLE_PLACEMENT_ATTEMPTS {
+            // Random position on the planet.
+            let mut angle = rng.gen_range(arc_from..=arc_to);
+
+            if let Some(last_obstacle_angle) = last_obstacle_angle {
+                if (angle - last_obstacle_angle).abs() < OBSTACLE_CLOSE_GAP_RANGE.1 {
+                    angle -= rng.gen_range(OBSTACLE_CLOSE_GAP_RANGE.0..OBSTACLE_CLOSE_GAP_RANGE.1);
+                } else if (angle - last_obstacle_angle).abs() < OBSTACLE_LONG_GAP_RANGE.1 {
+                    angle -= rng.gen_range(OBSTACLE_LONG_GAP_RANGE.0..OBSTACLE_LONG_GAP_RANGE.1);
                 }
             }
+
+            angle = angle.clamp(arc_from, arc_to);
+
+            let overlaps = angles
+                .iter()
+                .any(|&placed_angle| angle_distance(angle, placed_angle) < OBSTACLE_MIN_GAP);
+            if overlaps {
+                continue;
+            }
+
+            trace!(
+                "Last angle | New angle: {:?} , {}",
+                last_obstacle_angle,
+                angle
+            );
+
+            last_obstacle_angle = Some(angle);
+            angles.push(angle.rem_euclid(2. * PI));
+            is_placed = true;
+            break;
+        }
+
+        if !is_placed {
+            warn!(
+                "Dropped an obstacle after {} placement attempts, {} of {} placed",
+                OBSTACLE_PLACEMENT_ATTEMPTS,
+                angles.len(),
+                count
+            );
         }
     }
+
+    angles
 }
 
-fn spawn_background(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(AudioBundle {
-        source: asset_server.load("sounds/2021-10-19_-_Funny_Bit_-_www.FesliyanStudios.com.ogg"),
-        settings: PlaybackSettings {
-            mode: bevy::audio::PlaybackMode::Loop,
-            volume: Volume::Absolute(VolumeLevel::new(0.25)),
+/// Most obstacles the random placement fits into the `arc`. Counted at twice the
+/// `OBSTACLE_MIN_GAP`, since random angles can't pack them as tightly as possible.
+fn max_obstacles_in_arc(arc: (f32, f32)) -> usize {
+    ((arc.1 - arc.0) / (2. * OBSTACLE_MIN_GAP)) as usize + 1
+}
+
+/// Shortest distance between two angles (in radians) around the circle.
+fn angle_distance(first: f32, second: f32) -> f32 {
+    let distance = (first - second).rem_euclid(2. * PI);
+    distance.min(2. * PI - distance)
+}
+
+// When the new planet appears, it is filled with new obstacles. They're queued here
+// and spawned by `spawn_queued_obstacles`, then stay dormant until the player lands,
+// see `wake_dormant_obstacles`. The textures are preloaded, so the faint obstacles
+// can be read on the incoming planet during the camera pan.
+#[allow(clippy::too_many_arguments)]
+fn spawn_obstacles(
+    planet_query: Query<(Entity, &Planet), Without<ClearedPlanet>>,
+    mut obstacle_spawn_queue: ResMut<ObstacleSpawnQueue>,
+    mut loading: ResMut<AssetsLoading>,
+    mut game_rng: ResMut<GameRng>,
+    mut game_manager: ResMut<GameManager>,
+    asset_server: Res<AssetServer>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    planet_definitions: Res<PlanetDefinitions>,
+) {
+    debug!(
+        "Num of planets when spawning obstacles: {}",
+        planet_query.iter().len()
+    );
+
+    if let Ok((planet_entity, planet_struct)) = planet_query.get_single() {
+        let planet_def = planet_definitions.get(planet_struct.variant);
+        let texture = asset_server.load(planet_def.obstacle_texture.as_str());
+        let rotates = planet_def.obstacle_rotates;
+        let rng = &mut game_rng.rng;
+        let mut angles = if game_manager.infinite_mode {
+            let obstacles_num = rng
+                .gen_range(1..=obstacles_max_num_for_radius(planet_struct.radius, &game_balance));
+            let cluster_pattern = *ClusterPattern::ALL
+                .choose(rng)
+                .unwrap_or(&ClusterPattern::Scattered);
+            debug!("Obstacle pattern: {:?}", cluster_pattern);
+
+            cluster_pattern.angles(rng, obstacles_num, planet_def.obstacle_arc_radians())
+        } else if settings.shifted_story {
+            // Rolled here, after a replay has seeded the `GameRng`.
+            let story_angle_offset = *game_manager
+                .story_angle_offset
+                .get_or_insert_with(|| rng.gen_range(0. ..2. * PI));
+
+            shift_story_angles(
+                &planet_def.wave_obstacles(planet_struct.wave),
+                story_angle_offset,
+            )
+        } else {
+            planet_def.wave_obstacles(planet_struct.wave)
+        };
+
+        // Onboarding: the first planet of a run has at most one slow obstacle.
+        let is_first_planet_grace =
+            settings.first_planet_grace && game_manager.planets_cleared == 0;
+        let obstacle_speed = if is_first_planet_grace {
+            angles.truncate(1);
+            game_balance.obstacle_movement_speed * FIRST_PLANET_OBSTACLE_SPEED_SCALE
+        } else {
+            game_balance.obstacle_movement_speed
+        };
+
+        // The free arc around the landing spot on top is mirrored along with the obstacles.
+        if settings.mirror_level {
+            for angle in angles.iter_mut() {
+                *angle = mirror_angle(*angle);
+            }
+        }
+
+        for angle in angles {
+            // Story mode keeps every obstacle orbiting the same way.
+            let is_reversed = game_manager.infinite_mode
+                && !is_first_planet_grace
+                && rng.gen_bool(game_balance.obstacle_reverse_chance);
+            // The mirrored level orbits the other way.
+            let angular_velocity = if is_reversed != settings.mirror_level {
+                obstacle_speed
+            } else {
+                -obstacle_speed
+            };
+
+            let is_shooter = game_manager.infinite_mode
+                && game_manager.progress_score() >= game_balance.shooter_min_score
+                && rng.gen_bool(game_balance.shooter_chance);
+
+            let is_decoy = game_manager.infinite_mode
+                && !is_shooter
+                && game_manager.progress_score() >= game_balance.decoy_min_score
+                && rng.gen_bool(game_balance.decoy_chance);
+
+            obstacle_spawn_queue.pending.push_back(PendingObstacle {
+                planet: planet_entity,
+                angle,
+                angular_velocity,
+                rotates,
+                is_shooter,
+                is_decoy,
+                texture: texture.clone(),
+                frames: planet_def.obstacle_frames,
+            });
+        }
+
+        loading.0.push(texture.clone_untyped());
+    }
+}
+
+// Spawns `OBSTACLE_SPAWNS_PER_FRAME` of the queued obstacles every frame, or all of them
+// with the staggered spawn setting off. Obstacles of a planet that's gone are dropped.
+// Runs once the loading is done, so the obstacles don't wait on the textures.
+fn spawn_queued_obstacles(
+    mut commands: Commands,
+    mut obstacle_spawn_queue: ResMut<ObstacleSpawnQueue>,
+    mut planet_query: Query<(&Transform, &mut Planet)>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    images: Res<Assets<Image>>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+) {
+    let spawn_count = if settings.stagger_obstacle_spawn {
+        OBSTACLE_SPAWNS_PER_FRAME
+    } else {
+        obstacle_spawn_queue.pending.len()
+    };
+
+    for _ in 0..spawn_count {
+        let Some(pending_obstacle) = obstacle_spawn_queue.pending.pop_front() else {
+            return;
+        };
+
+        let Ok((planet_transform, mut planet_struct)) =
+            planet_query.get_mut(pending_obstacle.planet)
+        else {
+            continue;
+        };
+
+        let PendingObstacle {
+            angle,
+            angular_velocity,
+            rotates,
+            is_shooter,
+            is_decoy,
+            texture,
+            frames,
+            ..
+        } = pending_obstacle;
+
+        // The frame size comes from the texture. One that failed to load gets
+        // a single blank frame, the obstacle still collides.
+        let (frame_size, frames) = match images.get(&texture) {
+            Some(image) => (
+                Vec2::new(image.size().x / frames as f32, image.size().y),
+                frames,
+            ),
+            None => (OBSTACLE_SIZE, 1),
+        };
+
+        let texture_atlas = texture_atlases.add(TextureAtlas::from_grid(
+            texture, frame_size, frames, 1, None, None,
+        ));
+
+        let mut obstacle_position = Vec3::ZERO;
+
+        let planet_radius = planet_struct.radius;
+        let obstacle_radius = OBSTACLE_SIZE.y / 2.;
+
+        obstacle_position.x =
+            planet_transform.translation.x + angle.cos() * (planet_radius + obstacle_radius);
+        obstacle_position.y =
+            planet_transform.translation.y + angle.sin() * (planet_radius + obstacle_radius);
+
+        let mut obstacle = commands.spawn((
+            SpriteSheetBundle {
+                transform: Transform::from_translation(obstacle_position)
+                    .with_rotation(obstacle_rotation(angle, rotates)),
+                texture_atlas,
+                sprite: TextureAtlasSprite {
+                    custom_size: Some(OBSTACLE_SIZE),
+                    // The texture faces right, which is clockwise on top of the planet.
+                    flip_x: rotates && angular_velocity > 0.,
+                    color: if is_shooter {
+                        SHOOTER_COLOR
+                    } else if is_decoy {
+                        DECOY_COLOR
+                    } else {
+                        Color::WHITE
+                    }
+                    .with_a(0.),
+                    ..default()
+                },
+                ..default()
+            },
+            Collider {
+                shape: Ball::new(
+                    (OBSTACLE_SIZE.y / 2. - OBSTACLE_COLLIDER_INSET) * settings.hitbox_scale,
+                ),
+            },
+            Obstacle {
+                angle,
+                angular_velocity,
+                rotates,
+                lethal: !is_decoy,
+            },
+            ObstacleFadeIn {
+                timer: Timer::from_seconds(OBSTACLE_FADE_IN_DURATION, TimerMode::Once),
+            },
+        ));
+
+        if is_shooter {
+            obstacle.insert(Shooter {
+                fire_timer: Timer::from_seconds(
+                    game_balance.shooter_fire_interval,
+                    TimerMode::Repeating,
+                ),
+            });
+        }
+
+        if !planet_struct.is_playing {
+            obstacle.insert(ObstacleDormant);
+        }
+
+        if frames > 1 {
+            obstacle.insert(AnimatedSprite {
+                timer: Timer::from_seconds(OBSTACLE_ANIMATION_FRAME_TIME, TimerMode::Repeating),
+                frames,
+            });
+        }
+
+        planet_struct.obstacles.push(obstacle.id());
+    }
+}
+
+/// Despawns the planet with its children, the faces and the gravity well. Its obstacles
+/// go separately, see `despawn_obstacles`.
+fn despawn_planet(commands: &mut Commands, planet_entity: Entity, planet_struct: &Planet) {
+    despawn_obstacles(commands, &planet_struct.obstacles);
+    commands.entity(planet_entity).despawn_recursive();
+}
+
+/// Despawns the obstacles of a planet. They're top-level entities tracked in `Planet::obstacles`,
+/// never children of the planet, so despawning the planet can't take anything else with it.
+/// Obstacles that are already gone, like after a restart, are skipped.
+fn despawn_obstacles(commands: &mut Commands, obstacles: &[Entity]) {
+    for &obstacle_entity in obstacles {
+        if let Some(obstacle) = commands.get_entity(obstacle_entity) {
+            obstacle.despawn_recursive();
+        }
+    }
+}
+
+/// Real frame time, capped at `MAX_GAMEPLAY_DELTA`. The gameplay systems read `GameTime`,
+/// only the hit-stop, which freezes it, ticks on this.
+fn gameplay_delta(time: &Time) -> Duration {
+    time.delta()
+        .min(Duration::from_secs_f32(MAX_GAMEPLAY_DELTA))
+}
+
+// Stands the obstacle on the planet surface, with its feet toward the center.
+fn obstacle_rotation(angle: f32, rotates: bool) -> Quat {
+    if rotates {
+        Quat::from_rotation_z(angle - FRAC_PI_2)
+    } else {
+        Quat::IDENTITY
+    }
+}
+
+fn move_obstacles_on_planet(
+    mut children_query: Query<(&mut Transform, &mut Obstacle)>,
+    planet_query: Query<(&Planet, &Transform), Without<Obstacle>>,
+    current_planet: Res<CurrentPlanet>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    run_modifiers: Res<RunModifiers>,
+    game_time: Res<GameTime>,
+) {
+    let Some(planet_entity) = current_planet.entity else {
+        return;
+    };
+
+    if let Ok((planet_struct, planet_transform)) = planet_query.get(planet_entity) {
+        for &child in planet_struct.obstacles.iter() {
+            let child_query = children_query.get_mut(child);
+
+            if let Ok((mut transform, mut obstacle_struct)) = child_query {
+                let angle_delta = obstacle_angular_velocity(
+                    &obstacle_struct,
+                    planet_struct,
+                    &game_balance,
+                    &settings,
+                    &run_modifiers,
+                ) * game_time.delta_seconds();
+
+                orbit_obstacle(
+                    &mut transform,
+                    &mut obstacle_struct,
+                    planet_transform.translation,
+                    current_planet.radius,
+                    angle_delta,
+                );
+            }
+        }
+    }
+}
+
+/// Radians per second the obstacle moves around the planet. Obstacles fixed to the planet
+/// turn by exactly as much as `rotate_planets` turns the planet.
+fn obstacle_angular_velocity(
+    obstacle_struct: &Obstacle,
+    planet_struct: &Planet,
+    game_balance: &GameBalance,
+    settings: &GameSettings,
+    run_modifiers: &RunModifiers,
+) -> f32 {
+    if settings.obstacles_follow_planet {
+        planet_rotation_speed(game_balance, settings, run_modifiers)
+            * planet_struct.rotation_factor()
+    } else {
+        obstacle_struct.angular_velocity
+            * settings.obstacle_speed_scale
+            * run_modifiers.obstacle_speed_scale()
+    }
+}
+
+/// Puts the obstacle on the surface of the planet at its angle and moves it `angle_delta`
+/// radians along the orbit.
+fn orbit_obstacle(
+    transform: &mut Transform,
+    obstacle_struct: &mut Obstacle,
+    planet_translation: Vec3,
+    planet_radius: f32,
+    angle_delta: f32,
+) {
+    let obstacle_radius = OBSTACLE_SIZE.y / 2.;
+
+    transform.translation.x =
+        planet_translation.x + obstacle_struct.angle.cos() * (planet_radius + obstacle_radius);
+    transform.translation.y =
+        planet_translation.y + obstacle_struct.angle.sin() * (planet_radius + obstacle_radius);
+    transform.rotation = obstacle_rotation(obstacle_struct.angle, obstacle_struct.rotates);
+
+    // Wrap instead of resetting, so the obstacle keeps orbiting smoothly past 0 degrees.
+    obstacle_struct.angle = (obstacle_struct.angle + angle_delta).rem_euclid(2. * PI);
+}
+
+fn fade_in_obstacles(
+    mut commands: Commands,
+    mut obstacle_query: Query<(
+        Entity,
+        &mut TextureAtlasSprite,
+        &mut ObstacleFadeIn,
+        Option<&ObstacleDormant>,
+    )>,
+    game_time: Res<GameTime>,
+) {
+    for (obstacle_entity, mut obstacle_sprite, mut fade_in, dormant) in obstacle_query.iter_mut() {
+        let max_alpha = if dormant.is_some() {
+            DORMANT_OBSTACLE_ALPHA
+        } else {
+            1.
+        };
+
+        fade_in.timer.tick(game_time.delta());
+        obstacle_sprite
+            .color
+            .set_a(fade_in.timer.percent() * max_alpha);
+
+        if fade_in.timer.finished() {
+            commands.entity(obstacle_entity).remove::<ObstacleFadeIn>();
+        }
+    }
+}
+
+fn animate_sprites(
+    mut sprite_query: Query<(&mut TextureAtlasSprite, &mut AnimatedSprite)>,
+    game_time: Res<GameTime>,
+) {
+    for (mut sprite, mut animated_sprite) in sprite_query.iter_mut() {
+        let frames_passed = animated_sprite
+            .timer
+            .tick(game_time.delta())
+            .times_finished_this_tick() as usize;
+
+        if frames_passed > 0 {
+            sprite.index = (sprite.index + frames_passed) % animated_sprite.frames;
+        }
+    }
+}
+
+/// Seconds the free fall from `PLAYER_START_POSITION` onto the first planet takes.
+fn first_landing_time(game_balance: &GameBalance, gravity_scale: f32) -> f32 {
+    let fall_distance = PLAYER_START_POSITION.y - PLANET_SIZE.y / 2. - PLAYER_SIZE.y / 2.;
+
+    (2. * fall_distance / (game_balance.gravity_strength * gravity_scale).abs()).sqrt()
+}
+
+/// Height of a jump from the ground, in pixels.
+fn jump_peak_height(
+    game_balance: &GameBalance,
+    gravity_scale: f32,
+    jump_strength_scale: f32,
+) -> f32 {
+    (game_balance.player_jump_strength * jump_strength_scale).powi(2)
+        / (2. * (game_balance.gravity_strength * gravity_scale).abs())
+}
+
+/// Height of the lowest variable jump, with the key released right after the takeoff.
+fn short_jump_peak_height(
+    game_balance: &GameBalance,
+    gravity_scale: f32,
+    jump_strength_scale: f32,
+) -> f32 {
+    jump_peak_height(game_balance, gravity_scale, jump_strength_scale)
+        * game_balance.player_jump_cut.powi(2)
+}
+
+// The first planet spawns right below the start, so the player always falls onto it.
+// In case they don't, the player is put on top of it after a timeout.
+fn ensure_first_landing(
+    mut player_query: Query<(&mut Transform, &mut Player)>,
+    planet_query: Query<(&Transform, &Planet), Without<Player>>,
+    game_manager: Res<GameManager>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    game_time: Res<GameTime>,
+    mut airborne_time: Local<f32>,
+) {
+    let Ok((mut player_transform, mut player_struct)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let has_landed = player_struct.is_grounded
+        || game_manager.planets_cleared > 0
+        || planet_query
+            .iter()
+            .any(|(_, planet_struct)| planet_struct.is_playing);
+
+    // The player is parked at the start without planets, see `player_jump`.
+    if has_landed || planet_query.is_empty() {
+        *airborne_time = 0.;
+        return;
+    }
+
+    *airborne_time += game_time.delta_seconds();
+
+    if *airborne_time
+        < first_landing_time(&game_balance, settings.gravity_scale) * FIRST_LANDING_TIMEOUT_SCALE
+    {
+        return;
+    }
+
+    let Some((planet_transform, planet_struct)) = planet_query.iter().next() else {
+        return;
+    };
+
+    warn!("The player didn't reach the first planet, putting them on top of it");
+
+    player_transform.translation.x = planet_transform.translation.x;
+    player_transform.translation.y =
+        planet_transform.translation.y + planet_struct.radius + PLAYER_SIZE.y / 2.;
+    player_struct.velocity = 0.;
+    *airborne_time = 0.;
+}
+
+// Stretches the player sprite with the speed in the air and squashes it on landing,
+// easing back to the normal shape. The width makes up for the height, so the area stays.
+// Only the sprite changes, the collider keeps its radius.
+fn squash_and_stretch_player(
+    mut player_query: Query<(&mut Transform, &Player)>,
+    mut player_landed_event_reader: EventReader<PlayerLandedEvent>,
+    game_time: Res<GameTime>,
+) {
+    let Ok((mut player_transform, player_struct)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    if player_landed_event_reader.iter().next().is_some() {
+        player_transform.scale.y = 1. - PLAYER_LANDING_SQUASH;
+    }
+
+    let target_stretch = if player_struct.is_grounded {
+        1.
+    } else {
+        1. + (player_struct.velocity.abs() * PLAYER_STRETCH_PER_VELOCITY).min(PLAYER_MAX_STRETCH)
+    };
+
+    let smoothing = (PLAYER_SQUASH_SMOOTHING * game_time.delta_seconds()).min(1.);
+    let stretch = (player_transform.scale.y
+        + (target_stretch - player_transform.scale.y) * smoothing)
+        .clamp(1. - PLAYER_LANDING_SQUASH, 1. + PLAYER_MAX_STRETCH);
+
+    player_transform.scale = Vec3::new(1. / stretch, stretch, 1.);
+}
+
+// Lets the obstacles of the planet move and hurt, once the player lands on it.
+// They fade the rest of the way in, which gives the player a moment after landing.
+fn wake_dormant_obstacles(
+    mut commands: Commands,
+    mut planet_activated_event_reader: EventReader<PlanetActivatedEvent>,
+    planet_query: Query<&Planet>,
+    dormant_query: Query<(), With<ObstacleDormant>>,
+) {
+    for planet_activated_event in planet_activated_event_reader.iter() {
+        let Ok(planet_struct) = planet_query.get(planet_activated_event.planet) else {
+            continue;
+        };
+
+        for &obstacle_entity in planet_struct.obstacles.iter() {
+            if !dormant_query.contains(obstacle_entity) {
+                continue;
+            }
+
+            let mut fade_in_timer = Timer::from_seconds(OBSTACLE_FADE_IN_DURATION, TimerMode::Once);
+            fade_in_timer.set_elapsed(Duration::from_secs_f32(
+                OBSTACLE_FADE_IN_DURATION * DORMANT_OBSTACLE_ALPHA,
+            ));
+
+            commands
+                .entity(obstacle_entity)
+                .remove::<ObstacleDormant>()
+                .insert(ObstacleFadeIn {
+                    timer: fade_in_timer,
+                });
+        }
+    }
+}
+
+// Shooters on the active planet fire at the current position of the player.
+#[allow(clippy::too_many_arguments)]
+fn fire_projectiles(
+    mut commands: Commands,
+    mut shooter_query: Query<(&Transform, &mut Shooter)>,
+    planet_query: Query<&Planet>,
+    player_query: Query<&Transform, (With<Player>, Without<Shooter>)>,
+    asset_server: Res<AssetServer>,
+    game_balance: Res<GameBalance>,
+    settings: Res<GameSettings>,
+    game_time: Res<GameTime>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for planet_struct in planet_query.iter() {
+        if !planet_struct.is_playing {
+            continue;
+        }
+
+        for &obstacle_entity in planet_struct.obstacles.iter() {
+            let Ok((shooter_transform, mut shooter)) = shooter_query.get_mut(obstacle_entity)
+            else {
+                continue;
+            };
+
+            if !shooter.fire_timer.tick(game_time.delta()).just_finished() {
+                continue;
+            }
+
+            let direction = (player_transform.translation - shooter_transform.translation)
+                .truncate()
+                .normalize_or_zero();
+
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform::from_translation(shooter_transform.translation),
+                    texture: asset_server.load("art/ball.png"),
+                    sprite: Sprite {
+                        custom_size: Some(PROJECTILE_SIZE),
+                        color: SHOOTER_COLOR,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Collider {
+                    shape: Ball::new(PROJECTILE_SIZE.y / 2. * settings.hitbox_scale),
+                },
+                Projectile {
+                    velocity: direction * game_balance.projectile_speed,
+                    lifetime: Timer::from_seconds(
+                        game_balance.projectile_lifetime,
+                        TimerMode::Once,
+                    ),
+                },
+            ));
+        }
+    }
+}
+
+// Moves projectiles and despawns them, when they leave the screen or expire.
+fn move_projectiles(
+    mut commands: Commands,
+    mut projectile_query: Query<(Entity, &mut Transform, &mut Projectile)>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Projectile>)>,
+    game_time: Res<GameTime>,
+) {
+    let camera_translation = camera_query
+        .get_single()
+        .map_or(Vec3::ZERO, |camera_transform| camera_transform.translation);
+
+    for (projectile_entity, mut transform, mut projectile) in projectile_query.iter_mut() {
+        transform.translation += (projectile.velocity * game_time.delta_seconds()).extend(0.);
+
+        let is_off_screen = transform
+            .translation
+            .truncate()
+            .distance(camera_translation.truncate())
+            > BACKGROUND_SIZE.x;
+
+        if projectile.lifetime.tick(game_time.delta()).finished() || is_off_screen {
+            commands.entity(projectile_entity).despawn_recursive();
+        }
+    }
+}
+
+fn check_player_projectile_collisions(
+    mut player_hit_event_writer: EventWriter<PlayerHitEvent>,
+    player_query: Query<(&Collider, &Transform), With<Player>>,
+    projectile_query: Query<(&Collider, &Transform), With<Projectile>>,
+) {
+    for (player_collider, player_transform) in player_query.iter() {
+        for (projectile_collider, projectile_transform) in projectile_query.iter() {
+            let collision = collider_contact(
+                player_transform,
+                player_collider,
+                projectile_transform,
+                projectile_collider,
+                OBSTACLE_CONTACT_MARGIN,
+            );
+
+            if let Some(contact) = collision {
+                player_hit_event_writer.send(PlayerHitEvent {
+                    cause: DeathCause::ProjectileHit,
+                    contact_point: Some(contact_point(&contact)),
+                });
+            }
+        }
+    }
+}
+
+// Ends the run, when the player falls past all planets, e.g. after dashing off the landing spot.
+fn check_player_out_of_bounds(
+    mut player_hit_event_writer: EventWriter<PlayerHitEvent>,
+    player_query: Query<&Transform, With<Player>>,
+    planet_query: Query<(&Transform, &Planet)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let lowest_planet_bottom = planet_query
+        .iter()
+        .map(|(planet_transform, planet_struct)| {
+            planet_transform.translation.y - planet_struct.radius
+        })
+        .reduce(f32::min);
+
+    // No planets while the next one is loading.
+    let Some(lowest_planet_bottom) = lowest_planet_bottom else {
+        return;
+    };
+
+    if player_transform.translation.y < lowest_planet_bottom - PLAYER_FALL_LIMIT {
+        info!("Player has fallen into the void!");
+        player_hit_event_writer.send(PlayerHitEvent {
+            cause: DeathCause::FellIntoVoid,
+            contact_point: None,
+        });
+    }
+}
+
+// Starts the chosen track on startup and whenever the setting changes.
+fn switch_music_track(
+    mut commands: Commands,
+    music_query: Query<(Entity, &Music)>,
+    game_assets: Res<GameAssets>,
+    settings: Res<GameSettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let playing_music = music_query.get_single().ok();
+
+    let track = settings.music;
+
+    if let Some((music_entity, music)) = playing_music {
+        if music.track == track {
+            return;
+        }
+
+        commands.entity(music_entity).despawn_recursive();
+    }
+
+    commands.spawn((
+        AudioBundle {
+            source: game_assets.music(track),
+            settings: PlaybackSettings {
+                mode: bevy::audio::PlaybackMode::Loop,
+                volume: Volume::Absolute(VolumeLevel::new(
+                    MUSIC_BASE_VOLUME * settings.music_volume,
+                )),
+                ..default()
+            },
             ..default()
         },
-        ..default()
-    });
+        Music { track },
+    ));
+}
 
+fn spawn_background(mut commands: Commands, asset_server: Res<AssetServer>) {
     let tween = Tween::new(
         EaseFunction::QuadraticInOut,
         Duration::from_secs(0),
@@ -862,3 +3249,446 @@ fn spawn_background(mut commands: Commands, asset_server: Res<AssetServer>) {
         Animator::new(tween),
     ));
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::settings::DifficultyPreset;
+
+    const TEST_SEEDS: [u64; 5] = [0, 1, 7, 42, 2023];
+    // Wrapping the angles into 0..2π can round them off a little.
+    const ANGLE_TOLERANCE: f32 = 1e-4;
+    const TEST_FRAME_TIME: f32 = 1. / 60.;
+
+    /// Moves and lands the player on a fixed frame time, without a window or rendering.
+    fn player_test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, GameTimePlugin))
+            .add_state::<PauseState>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                TEST_FRAME_TIME,
+            )))
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<GameBalance>()
+            .init_resource::<GameSettings>()
+            .init_resource::<RunModifiers>()
+            .init_resource::<CurrentPlanet>()
+            .insert_resource(GameAssets {
+                font: Handle::default(),
+                jump_sounds: Vec::new(),
+                music_tracks: Vec::new(),
+            })
+            .add_event::<PlayerJumpedEvent>()
+            .add_event::<PlayerLandedEvent>()
+            .add_event::<PlanetActivatedEvent>()
+            .add_systems(
+                Update,
+                (player_jump, check_player_planet_collisions).chain(),
+            );
+
+        app
+    }
+
+    fn spawn_test_player(world: &mut World, translation: Vec3) -> Entity {
+        world
+            .spawn((
+                Transform::from_translation(translation),
+                Player {
+                    is_grounded: false,
+                    velocity: 0.,
+                    dash_cooldown: Timer::from_seconds(1., TimerMode::Once),
+                },
+                Collider {
+                    shape: Ball::new(PLAYER_SIZE.y / 2. - PLAYER_COLLIDER_INSET),
+                },
+            ))
+            .id()
+    }
+
+    fn spawn_test_planet(world: &mut World, translation: Vec3) -> Entity {
+        let radius = PLANET_SIZE.y / 2.;
+
+        world
+            .spawn((
+                Transform::from_translation(translation),
+                Planet {
+                    variant: PlanetVariant::Earth,
+                    is_playing: false,
+                    obstacles: Vec::new(),
+                    radius,
+                    wave: 0,
+                    wave_obstacles_dodged: 0,
+                    rotation_ease: 0.,
+                },
+                Collider {
+                    shape: Ball::new(radius),
+                },
+            ))
+            .id()
+    }
+
+    #[test]
+    fn generated_obstacles_keep_the_minimum_spacing() {
+        for seed in TEST_SEEDS {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let angles = generate_obstacle_angles(&mut rng, OBSTACLES_MAX_NUM, OBSTACLE_FULL_ARC);
+
+            assert!(!angles.is_empty(), "seed {}", seed);
+            for pair in angles.windows(2) {
+                assert!(
+                    angle_distance(pair[0], pair[1]) >= OBSTACLE_MIN_GAP - ANGLE_TOLERANCE,
+                    "seed {}: {:?}",
+                    seed,
+                    angles
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generated_obstacles_only_depend_on_the_seed() {
+        for seed in TEST_SEEDS {
+            let first = generate_obstacle_angles(
+                &mut StdRng::seed_from_u64(seed),
+                OBSTACLES_MAX_NUM,
+                OBSTACLE_FULL_ARC,
+            );
+            let second = generate_obstacle_angles(
+                &mut StdRng::seed_from_u64(seed),
+                OBSTACLES_MAX_NUM,
+                OBSTACLE_FULL_ARC,
+            );
+
+            assert_eq!(first, second, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn obstacle_orbits_past_a_full_turn_without_jumping() {
+        let planet_radius = PLANET_SIZE.y / 2.;
+        let orbit_radius = planet_radius + OBSTACLE_SIZE.y / 2.;
+
+        // Both ways across 0 degrees, starting a few steps before it.
+        for (start_angle, angle_delta) in [(2. * PI - 0.05, 0.01), (0.05, -0.01)] {
+            let mut transform = Transform::default();
+            let mut obstacle = Obstacle {
+                angle: start_angle,
+                angular_velocity: 0.,
+                rotates: true,
+                lethal: true,
+            };
+            let mut last_position: Option<Vec3> = None;
+
+            for _ in 0..10 {
+                orbit_obstacle(
+                    &mut transform,
+                    &mut obstacle,
+                    Vec3::ZERO,
+                    planet_radius,
+                    angle_delta,
+                );
+
+                if let Some(last_position) = last_position {
+                    let step = transform.translation.distance(last_position);
+                    assert!(
+                        step <= orbit_radius * f32::abs(angle_delta) + 1e-3,
+                        "jumped {} at angle {}",
+                        step,
+                        obstacle.angle
+                    );
+                }
+                assert!((0. ..2. * PI).contains(&obstacle.angle));
+
+                last_position = Some(transform.translation);
+            }
+        }
+    }
+
+    #[test]
+    fn crowded_obstacles_never_overlap() {
+        assert!(OBSTACLE_CLOSE_GAP_RANGE.0 > 0.);
+
+        for seed in TEST_SEEDS {
+            let mut rng = StdRng::seed_from_u64(seed);
+            // More than fit in the arc, so the count is cut down and some angles are rerolled.
+            let angles = generate_obstacle_angles(&mut rng, 40, OBSTACLE_FULL_ARC);
+
+            assert!(angles.len() <= max_obstacles_in_arc(OBSTACLE_FULL_ARC));
+
+            for (index, &angle) in angles.iter().enumerate() {
+                for &other_angle in &angles[index + 1..] {
+                    assert!(
+                        angle_distance(angle, other_angle) >= OBSTACLE_MIN_GAP - ANGLE_TOLERANCE,
+                        "seed {}: {} and {}",
+                        seed,
+                        angle,
+                        other_angle
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn face_changes_right_at_the_thresholds() {
+        assert_eq!(
+            face_state_for_radius(PLANET_FACE_NORMAL_THRESHOLD, PLANET_SHRINK_LIMIT),
+            (1, PlanetFaceState::Normal)
+        );
+        assert_eq!(
+            face_state_for_radius(PLANET_FACE_BAD_THRESHOLD, PLANET_SHRINK_LIMIT),
+            (2, PlanetFaceState::Bad)
+        );
+
+        for difficulty in [
+            DifficultyPreset::Normal,
+            DifficultyPreset::Hard,
+            DifficultyPreset::Survival,
+        ] {
+            let shrink_limit = difficulty.shrink_limit();
+            let normal_threshold = scale_face_threshold(PLANET_FACE_NORMAL_THRESHOLD, shrink_limit);
+            let bad_threshold = scale_face_threshold(PLANET_FACE_BAD_THRESHOLD, shrink_limit);
+
+            for (radius, face) in [
+                (PLANET_SIZE.y / 2., (0, PlanetFaceState::Good)),
+                (normal_threshold + 1., (0, PlanetFaceState::Good)),
+                (normal_threshold, (1, PlanetFaceState::Normal)),
+                (bad_threshold + 1., (1, PlanetFaceState::Normal)),
+                (bad_threshold, (2, PlanetFaceState::Bad)),
+                (shrink_limit.y / 2., (2, PlanetFaceState::Bad)),
+            ] {
+                assert_eq!(
+                    face_state_for_radius(radius, shrink_limit),
+                    face,
+                    "{:?} at radius {}",
+                    difficulty,
+                    radius
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn default_jump_peaks_at_the_documented_height() {
+        let game_balance = GameBalance::default();
+
+        // 450² / (2 * 752.4), see `PLAYER_JUMP_STRENGTH`.
+        assert!((jump_peak_height(&game_balance, 1., 1.) - 134.57).abs() < 0.01);
+        assert!(
+            (jump_peak_height(&game_balance, 2., 1.)
+                - jump_peak_height(&game_balance, 1., 1.) / 2.)
+                .abs()
+                < 0.01
+        );
+
+        // Steps the jump like `player_jump` does at 60 FPS, which peaks within a frame of it.
+        let delta_seconds = 1. / 60.;
+        let mut velocity = game_balance.player_jump_strength;
+        let mut height: f32 = 0.;
+        let mut peak_height: f32 = 0.;
+        while velocity > 0. {
+            velocity += game_balance.gravity_strength * delta_seconds;
+            height += velocity * delta_seconds;
+            peak_height = peak_height.max(height);
+        }
+
+        assert!(
+            (peak_height - jump_peak_height(&game_balance, 1., 1.)).abs()
+                < game_balance.player_jump_strength * delta_seconds,
+            "peaked at {}",
+            peak_height
+        );
+    }
+
+    #[test]
+    fn every_landing_sends_one_event() {
+        let mut app = player_test_app();
+        let planet_entity = spawn_test_planet(&mut app.world, Vec3::ZERO);
+        // A little above the surface, so the player lands within a few frames.
+        let player_entity = spawn_test_player(
+            &mut app.world,
+            Vec3::new(0., PLANET_SIZE.y / 2. + PLAYER_SIZE.y / 2. + 10., 0.),
+        );
+
+        let mut landed_event_reader = app
+            .world
+            .resource::<Events<PlayerLandedEvent>>()
+            .get_reader();
+        let mut count_landings = |app: &App| {
+            landed_event_reader
+                .iter(app.world.resource::<Events<PlayerLandedEvent>>())
+                .filter(|player_landed_event| player_landed_event.planet == planet_entity)
+                .count()
+        };
+
+        let mut landings = 0;
+        for _ in 0..60 {
+            app.update();
+            landings += count_landings(&app);
+        }
+        assert_eq!(landings, 1);
+        assert!(app.world.get::<Player>(player_entity).unwrap().is_grounded);
+
+        // Standing still sends nothing, the jump lands once more.
+        let jump_key = app.world.resource::<GameSettings>().key_bindings.jump;
+        app.world.resource_mut::<Input<KeyCode>>().press(jump_key);
+        app.update();
+        landings += count_landings(&app);
+        app.world.resource_mut::<Input<KeyCode>>().reset(jump_key);
+        assert!(!app.world.get::<Player>(player_entity).unwrap().is_grounded);
+
+        for _ in 0..120 {
+            app.update();
+            landings += count_landings(&app);
+        }
+        assert_eq!(landings, 2);
+        assert!(app.world.get::<Player>(player_entity).unwrap().is_grounded);
+    }
+
+    #[test]
+    fn player_lands_on_the_first_planet_in_time() {
+        let mut app = player_test_app();
+        // Where `start_game` spawns the first planet, right below the start.
+        spawn_test_planet(&mut app.world, Vec3::ZERO);
+        let player_entity = spawn_test_player(&mut app.world, PLAYER_START_POSITION);
+
+        let landing_time = first_landing_time(&GameBalance::default(), 1.);
+        let timeout = landing_time * FIRST_LANDING_TIMEOUT_SCALE;
+
+        let mut elapsed = 0.;
+        while !app.world.get::<Player>(player_entity).unwrap().is_grounded {
+            assert!(elapsed < timeout, "still falling after {} seconds", elapsed);
+
+            app.update();
+            elapsed += TEST_FRAME_TIME;
+        }
+
+        // The stepped fall is off by a few frames at most.
+        assert!(
+            (elapsed - landing_time).abs() < 3. * TEST_FRAME_TIME,
+            "landed after {} instead of {} seconds",
+            elapsed,
+            landing_time
+        );
+    }
+
+    #[test]
+    fn player_waits_at_the_start_until_the_first_planet_spawns() {
+        let mut app = player_test_app();
+        let player_entity = spawn_test_player(&mut app.world, PLAYER_START_POSITION);
+
+        // The loading window, there's no planet yet.
+        for _ in 0..30 {
+            app.update();
+        }
+
+        let player_transform = app.world.get::<Transform>(player_entity).unwrap();
+        assert_eq!(player_transform.translation, PLAYER_START_POSITION);
+        let player_struct = app.world.get::<Player>(player_entity).unwrap();
+        assert_eq!(player_struct.velocity, 0.);
+
+        // The fall starts with the planet.
+        spawn_test_planet(&mut app.world, Vec3::ZERO);
+        app.update();
+
+        let player_transform = app.world.get::<Transform>(player_entity).unwrap();
+        assert!(player_transform.translation.y < PLAYER_START_POSITION.y);
+    }
+
+    #[test]
+    fn despawning_a_planet_keeps_everything_else() {
+        fn despawn_planets(mut commands: Commands, planet_query: Query<(Entity, &Planet)>) {
+            for (planet_entity, planet_struct) in planet_query.iter() {
+                despawn_planet(&mut commands, planet_entity, planet_struct);
+            }
+        }
+
+        let mut app = App::new();
+        app.add_systems(Update, despawn_planets);
+
+        let world = &mut app.world;
+        let obstacles: Vec<Entity> = (0..3)
+            .map(|_| world.spawn(Transform::default()).id())
+            .collect();
+        let planet_entity = spawn_test_planet(world, Vec3::ZERO);
+        world.get_mut::<Planet>(planet_entity).unwrap().obstacles = obstacles.clone();
+        let mut children = Vec::new();
+        world.entity_mut(planet_entity).with_children(|parent| {
+            // The face, its blend overlay and the gravity well.
+            for _ in 0..3 {
+                children.push(parent.spawn(Transform::default()).id());
+            }
+        });
+
+        let kept_entities = [
+            spawn_test_player(world, PLAYER_START_POSITION),
+            world.spawn(Camera2dBundle::default()).id(),
+            world.spawn((Transform::default(), Background)).id(),
+            world.spawn((NodeBundle::default(), ScoreText)).id(),
+        ];
+
+        app.update();
+
+        assert!(app.world.get_entity(planet_entity).is_none());
+        for entity in children.iter().chain(obstacles.iter()) {
+            assert!(app.world.get_entity(*entity).is_none());
+        }
+        for entity in kept_entities {
+            assert!(app.world.get_entity(entity).is_some());
+        }
+    }
+
+    #[test]
+    fn shifted_story_obstacles_are_spaced_out_of_the_landing_spot() {
+        let (arc_from, arc_to) = STORY_LANDING_CLEAR_ARC;
+        // The turn moves all but the first onto the landing spot.
+        let angles: Vec<f32> = [330., 345., 0., 10.]
+            .into_iter()
+            .map(|angle: f32| angle.to_radians())
+            .collect();
+
+        let shifted_angles = shift_story_angles(&angles, FRAC_PI_2);
+
+        assert_eq!(shifted_angles.len(), angles.len());
+        for (index, &angle) in shifted_angles.iter().enumerate() {
+            assert!(
+                angle <= arc_from + ANGLE_TOLERANCE || angle >= arc_to - ANGLE_TOLERANCE,
+                "{} is on the landing spot",
+                angle
+            );
+
+            for &other_angle in &shifted_angles[index + 1..] {
+                assert!(
+                    angle_distance(angle, other_angle) >= OBSTACLE_MIN_GAP - ANGLE_TOLERANCE,
+                    "{:?}",
+                    shifted_angles
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn near_misses_dont_count_toward_the_thresholds() {
+        let mut app = App::new();
+        app.add_event::<ScoreEvent>()
+            .init_resource::<GameManager>()
+            .init_resource::<RunModifiers>()
+            .add_systems(Update, apply_score_events);
+
+        app.world.send_event(ScoreEvent {
+            points: 1,
+            reason: ScoreReason::NearMiss,
+        });
+        app.world.send_event(ScoreEvent {
+            points: 1,
+            reason: ScoreReason::PlanetCleared,
+        });
+        app.update();
+
+        let game_manager = app.world.resource::<GameManager>();
+        assert_eq!(game_manager.score, 2);
+        assert_eq!(game_manager.progress_score(), 1);
+    }
+}
@@ -0,0 +1,1213 @@
+use crate::{
+    assets::GameAssets,
+    balance::GameBalance,
+    leaderboard::Leaderboard,
+    modifiers::{RunModifier, RunModifiers},
+    planets::PlanetDefinitions,
+    progress::{Progress, Streak},
+    replay, save,
+    settings::{
+        next_hitbox_scale, next_letterbox, next_obstacle_speed_scale, next_screen_shake_intensity,
+        next_short_run_planets, next_ui_scale, next_volume_step, on_off, GameSettings, KeyBindings,
+        PlayerSkin,
+    },
+    ui::{HOVERED_BUTTON_COLOR, NORMAL_BUTTON_COLOR, PRESSED_BUTTON_COLOR},
+};
+
+use super::{AppState, NextRunSeed};
+use bevy::{prelude::*, time::TimeUpdateStrategy, window::ReceivedCharacter};
+
+pub const MENU_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.flex_direction = FlexDirection::Column;
+    style.justify_content = JustifyContent::Center;
+    style.align_items = AlignItems::Center;
+    style.width = Val::Percent(100.);
+    style.height = Val::Percent(100.);
+    style
+};
+
+pub const PLAY_BUTTON_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.width = Val::Px(200.);
+    style.height = Val::Px(200.);
+    style.margin = UiRect::bottom(Val::Px(24.));
+    style
+};
+
+pub const SETTING_BUTTON_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.justify_content = JustifyContent::Center;
+    style.align_items = AlignItems::Center;
+    style.width = Val::Px(380.);
+    style.height = Val::Px(52.);
+    style.margin = UiRect::all(Val::Px(6.));
+    style
+};
+
+pub const PANEL_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.flex_direction = FlexDirection::Column;
+    style.align_items = AlignItems::Center;
+    style
+};
+
+pub const SETTINGS_GRID_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.flex_direction = FlexDirection::Row;
+    style.flex_wrap = FlexWrap::Wrap;
+    style.justify_content = JustifyContent::Center;
+    style.max_width = Val::Px(800.);
+    style
+};
+
+/// Settings on one page of the settings panel, so it fits the window at any count.
+pub const SETTINGS_PER_PAGE: usize = 10;
+
+pub const SKIN_GRID_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.flex_direction = FlexDirection::Row;
+    style.justify_content = JustifyContent::Center;
+    style.margin = UiRect::all(Val::Px(6.));
+    style
+};
+
+pub const SKIN_BUTTON_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.justify_content = JustifyContent::Center;
+    style.align_items = AlignItems::End;
+    style.width = Val::Px(72.);
+    style.height = Val::Px(72.);
+    style.margin = UiRect::horizontal(Val::Px(8.));
+    style
+};
+
+// Digits of the largest `u64`.
+pub const SEED_MAX_DIGITS: usize = 20;
+
+pub const UNSELECTED_SKIN_COLOR: Color = Color::rgb(0.55, 0.55, 0.55);
+pub const LOCKED_SKIN_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+
+pub const MENU_BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.6);
+pub const NORMAL_SETTING_BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.3);
+pub const HOVERED_SETTING_BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.45);
+pub const PRESSED_SETTING_BUTTON_COLOR: Color = Color::rgb(0.35, 0.35, 0.6);
+
+#[derive(Component)]
+pub struct MainMenu;
+
+#[derive(Component)]
+pub struct PlayButton;
+
+/// Page of the menu. Only one is shown at a time.
+#[derive(Component, Clone, Copy, Eq, PartialEq)]
+pub enum MenuPanel {
+    /// The play button and the skins.
+    Main,
+    Settings,
+    /// Twists of the next run.
+    Modifiers,
+    Leaderboard,
+    /// Asks before deleting all saved data.
+    ConfirmReset,
+}
+
+impl MenuPanel {
+    /// Label of the button that opens the panel.
+    fn button_label(self) -> &'static str {
+        match self {
+            MenuPanel::Main => "Back",
+            MenuPanel::Settings => "Settings",
+            MenuPanel::Modifiers => "Modifiers",
+            MenuPanel::Leaderboard => "Leaderboard",
+            MenuPanel::ConfirmReset => "Reset all data",
+        }
+    }
+}
+
+/// Opens the panel and hides the others.
+#[derive(Component, Clone, Copy)]
+pub struct PanelButton(MenuPanel);
+
+#[derive(Component)]
+pub struct ResetButton;
+
+/// Part of the `SettingButton`s, only the shown page is displayed.
+#[derive(Component)]
+pub struct SettingsPage(usize);
+
+/// Flips to the next page of the settings, after the last one back to the first.
+#[derive(Component)]
+pub struct SettingsPageButton {
+    page: usize,
+}
+
+fn settings_page_count() -> usize {
+    SettingButton::ALL.len().div_ceil(SETTINGS_PER_PAGE)
+}
+
+fn settings_page_label(page: usize) -> String {
+    format!("Page {}/{}", page + 1, settings_page_count())
+}
+
+/// Plays back the last recorded run.
+#[derive(Component)]
+pub struct WatchReplayButton;
+
+/// Starts typing the seed of the next run, and confirms it when pressed again.
+#[derive(Component)]
+pub struct SeedButton;
+
+/// Digits of the seed being typed on the `SeedButton`.
+#[derive(Resource, Default)]
+pub struct SeedEntry {
+    pub digits: String,
+}
+
+#[derive(Component)]
+pub struct SkinButton(PlayerSkin);
+
+/// Turns a modifier of the next run on or off.
+#[derive(Component, Clone, Copy)]
+pub struct ModifierButton(RunModifier);
+
+fn modifier_label(modifier: RunModifier, run_modifiers: &RunModifiers) -> String {
+    format!(
+        "{}: {}",
+        modifier.name(),
+        on_off(run_modifiers.contains(modifier))
+    )
+}
+
+/// A menu button that cycles through the values of a single setting.
+#[derive(Component, Clone, Copy)]
+pub enum SettingButton {
+    Difficulty,
+    Controls,
+    InvertFastFall,
+    SfxVolume,
+    MusicVolume,
+    ShowColliders,
+    FirstPlanetGrace,
+    HitboxScale,
+    CameraFollow,
+    WarpEffect,
+    PauseOnFocusLoss,
+    ControlsHint,
+    ShortRun,
+    StoryEnding,
+    AutoJump,
+    UiScale,
+    InstantCameraCut,
+    StaggerObstacleSpawn,
+    RotateCamera,
+    ObstacleSpeed,
+    Letterbox,
+    SpeedrunOverlay,
+    Freeplay,
+    ScreenShake,
+    AirborneShrinkSlowdown,
+    MirrorLevel,
+    Feel,
+    ShiftedStory,
+    ObstacleTrajectories,
+    AngleGuide,
+    ObstacleMotion,
+    MenuFrameLimit,
+    VariableJump,
+}
+
+impl SettingButton {
+    pub const ALL: [SettingButton; 33] = [
+        SettingButton::Difficulty,
+        SettingButton::Controls,
+        SettingButton::InvertFastFall,
+        SettingButton::SfxVolume,
+        SettingButton::MusicVolume,
+        SettingButton::ShowColliders,
+        SettingButton::FirstPlanetGrace,
+        SettingButton::HitboxScale,
+        SettingButton::CameraFollow,
+        SettingButton::WarpEffect,
+        SettingButton::PauseOnFocusLoss,
+        SettingButton::ControlsHint,
+        SettingButton::ShortRun,
+        SettingButton::StoryEnding,
+        SettingButton::AutoJump,
+        SettingButton::UiScale,
+        SettingButton::InstantCameraCut,
+        SettingButton::StaggerObstacleSpawn,
+        SettingButton::RotateCamera,
+        SettingButton::ObstacleSpeed,
+        SettingButton::Letterbox,
+        SettingButton::SpeedrunOverlay,
+        SettingButton::Freeplay,
+        SettingButton::ScreenShake,
+        SettingButton::AirborneShrinkSlowdown,
+        SettingButton::MirrorLevel,
+        SettingButton::Feel,
+        SettingButton::ShiftedStory,
+        SettingButton::ObstacleTrajectories,
+        SettingButton::AngleGuide,
+        SettingButton::ObstacleMotion,
+        SettingButton::MenuFrameLimit,
+        SettingButton::VariableJump,
+    ];
+
+    fn label(self, settings: &GameSettings) -> String {
+        match self {
+            SettingButton::Difficulty => {
+                format!("Difficulty: {}", settings.difficulty.name())
+            }
+            SettingButton::Controls => {
+                format!("Controls: {}", settings.key_bindings.preset.name())
+            }
+            SettingButton::InvertFastFall => {
+                format!("Fast-fall key rises: {}", on_off(settings.invert_fast_fall))
+            }
+            SettingButton::SfxVolume => {
+                format!("SFX volume: {:.0}%", settings.sfx_volume * 100.)
+            }
+            SettingButton::MusicVolume => {
+                format!("Music volume: {:.0}%", settings.music_volume * 100.)
+            }
+            SettingButton::ShowColliders => {
+                format!("Show colliders: {}", on_off(settings.always_show_colliders))
+            }
+            SettingButton::FirstPlanetGrace => {
+                format!("Easy first planet: {}", on_off(settings.first_planet_grace))
+            }
+            SettingButton::HitboxScale => {
+                format!("Hazard hitbox: {:.0}%", settings.hitbox_scale * 100.)
+            }
+            SettingButton::CameraFollow => {
+                format!("Camera follow: {}", on_off(settings.camera_follow))
+            }
+            SettingButton::WarpEffect => {
+                format!("Warp effect: {}", on_off(settings.warp_effect))
+            }
+            SettingButton::PauseOnFocusLoss => {
+                format!(
+                    "Pause on focus loss: {}",
+                    on_off(settings.pause_on_focus_loss)
+                )
+            }
+            SettingButton::ControlsHint => {
+                format!("Controls hint: {}", on_off(settings.show_controls_hint))
+            }
+            SettingButton::ShortRun => match settings.short_run_planets {
+                Some(planets) => format!("Run length: {} planets", planets),
+                None => "Run length: Endless".to_string(),
+            },
+            SettingButton::StoryEnding => {
+                if settings.endless_after_story {
+                    "After the story: Endless".to_string()
+                } else {
+                    "After the story: Victory".to_string()
+                }
+            }
+            SettingButton::AutoJump => format!("Auto-jump: {}", on_off(settings.auto_jump)),
+            SettingButton::UiScale => match settings.ui_scale {
+                Some(ui_scale) => format!("UI scale: {:.0}%", ui_scale * 100.),
+                None => "UI scale: Auto".to_string(),
+            },
+            SettingButton::InstantCameraCut => {
+                format!(
+                    "Instant camera cut: {}",
+                    on_off(settings.instant_camera_cut)
+                )
+            }
+            SettingButton::StaggerObstacleSpawn => {
+                format!(
+                    "Staggered obstacles: {}",
+                    on_off(settings.stagger_obstacle_spawn)
+                )
+            }
+            SettingButton::RotateCamera => {
+                format!("Rotating camera: {}", on_off(settings.rotate_camera))
+            }
+            SettingButton::ObstacleSpeed => {
+                format!(
+                    "Obstacle speed: {:.0}%",
+                    settings.obstacle_speed_scale * 100.
+                )
+            }
+            SettingButton::Letterbox => match settings.letterbox {
+                Some(aspect_ratio) => format!("Letterbox: {}", aspect_ratio.name()),
+                None => "Letterbox: Off".to_string(),
+            },
+            SettingButton::SpeedrunOverlay => {
+                format!("Speedrun overlay: {}", on_off(settings.speedrun_overlay))
+            }
+            SettingButton::Freeplay => format!("Freeplay: {}", on_off(settings.freeplay)),
+            SettingButton::ScreenShake => match settings.screen_shake_intensity {
+                intensity if intensity <= 0. => "Screen shake: Off".to_string(),
+                intensity => format!("Screen shake: {:.0}%", intensity * 100.),
+            },
+            SettingButton::AirborneShrinkSlowdown => format!(
+                "Slower shrink mid-jump: {}",
+                on_off(settings.airborne_shrink_slowdown)
+            ),
+            SettingButton::MirrorLevel => {
+                format!("Mirrored level: {}", on_off(settings.mirror_level))
+            }
+            SettingButton::Feel => format!("Feel: {}", settings.feel.name()),
+            SettingButton::ShiftedStory => {
+                format!("Shifted story: {}", on_off(settings.shifted_story))
+            }
+            SettingButton::ObstacleTrajectories => format!(
+                "Obstacle paths: {}",
+                on_off(settings.show_obstacle_trajectories)
+            ),
+            SettingButton::AngleGuide => {
+                format!("Angle guide: {}", on_off(settings.show_angle_guide))
+            }
+            SettingButton::ObstacleMotion => format!(
+                "Obstacles: {}",
+                if settings.obstacles_follow_planet {
+                    "fixed to planet"
+                } else {
+                    "orbiting"
+                }
+            ),
+            SettingButton::MenuFrameLimit => {
+                format!("Menu frame limit: {}", on_off(settings.menu_frame_limit))
+            }
+            SettingButton::VariableJump => {
+                format!("Variable jump: {}", on_off(settings.variable_jump))
+            }
+        }
+    }
+
+    fn cycle(self, settings: &mut GameSettings) {
+        match self {
+            SettingButton::Difficulty => {
+                settings.difficulty = settings.difficulty.next();
+            }
+            SettingButton::Controls => {
+                settings.key_bindings =
+                    KeyBindings::from_preset(settings.key_bindings.preset.next());
+            }
+            SettingButton::InvertFastFall => {
+                settings.invert_fast_fall = !settings.invert_fast_fall;
+            }
+            SettingButton::SfxVolume => {
+                settings.sfx_volume = next_volume_step(settings.sfx_volume);
+            }
+            SettingButton::MusicVolume => {
+                settings.music_volume = next_volume_step(settings.music_volume);
+            }
+            SettingButton::ShowColliders => {
+                settings.always_show_colliders = !settings.always_show_colliders;
+            }
+            SettingButton::FirstPlanetGrace => {
+                settings.first_planet_grace = !settings.first_planet_grace;
+            }
+            SettingButton::HitboxScale => {
+                settings.hitbox_scale = next_hitbox_scale(settings.hitbox_scale);
+            }
+            SettingButton::CameraFollow => {
+                settings.camera_follow = !settings.camera_follow;
+            }
+            SettingButton::WarpEffect => {
+                settings.warp_effect = !settings.warp_effect;
+            }
+            SettingButton::PauseOnFocusLoss => {
+                settings.pause_on_focus_loss = !settings.pause_on_focus_loss;
+            }
+            SettingButton::ControlsHint => {
+                settings.show_controls_hint = !settings.show_controls_hint;
+            }
+            SettingButton::ShortRun => {
+                settings.short_run_planets = next_short_run_planets(settings.short_run_planets);
+            }
+            SettingButton::StoryEnding => {
+                settings.endless_after_story = !settings.endless_after_story;
+            }
+            SettingButton::AutoJump => {
+                settings.auto_jump = !settings.auto_jump;
+            }
+            SettingButton::UiScale => {
+                settings.ui_scale = next_ui_scale(settings.ui_scale);
+            }
+            SettingButton::InstantCameraCut => {
+                settings.instant_camera_cut = !settings.instant_camera_cut;
+            }
+            SettingButton::StaggerObstacleSpawn => {
+                settings.stagger_obstacle_spawn = !settings.stagger_obstacle_spawn;
+            }
+            SettingButton::RotateCamera => {
+                settings.rotate_camera = !settings.rotate_camera;
+            }
+            SettingButton::ObstacleSpeed => {
+                settings.obstacle_speed_scale =
+                    next_obstacle_speed_scale(settings.obstacle_speed_scale);
+            }
+            SettingButton::Letterbox => {
+                settings.letterbox = next_letterbox(settings.letterbox);
+            }
+            SettingButton::SpeedrunOverlay => {
+                settings.speedrun_overlay = !settings.speedrun_overlay;
+            }
+            SettingButton::Freeplay => {
+                settings.freeplay = !settings.freeplay;
+            }
+            SettingButton::ScreenShake => {
+                settings.screen_shake_intensity =
+                    next_screen_shake_intensity(settings.screen_shake_intensity);
+            }
+            SettingButton::AirborneShrinkSlowdown => {
+                settings.airborne_shrink_slowdown = !settings.airborne_shrink_slowdown;
+            }
+            SettingButton::MirrorLevel => {
+                settings.mirror_level = !settings.mirror_level;
+            }
+            SettingButton::Feel => {
+                settings.feel.next().apply(settings);
+            }
+            SettingButton::ShiftedStory => {
+                settings.shifted_story = !settings.shifted_story;
+            }
+            SettingButton::ObstacleTrajectories => {
+                settings.show_obstacle_trajectories = !settings.show_obstacle_trajectories;
+            }
+            SettingButton::AngleGuide => {
+                settings.show_angle_guide = !settings.show_angle_guide;
+            }
+            SettingButton::ObstacleMotion => {
+                settings.obstacles_follow_planet = !settings.obstacles_follow_planet;
+            }
+            SettingButton::MenuFrameLimit => {
+                settings.menu_frame_limit = !settings.menu_frame_limit;
+            }
+            SettingButton::VariableJump => {
+                settings.variable_jump = !settings.variable_jump;
+            }
+        }
+    }
+}
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Menu), spawn_main_menu)
+            .add_systems(
+                Update,
+                (
+                    interact_with_play_button,
+                    interact_with_panel_buttons,
+                    interact_with_setting_buttons,
+                    interact_with_reset_button,
+                    interact_with_settings_page_button,
+                    interact_with_watch_replay_button,
+                    interact_with_seed_button,
+                    type_seed,
+                    update_seed_label,
+                    update_setting_labels,
+                    interact_with_skin_buttons,
+                    interact_with_modifier_buttons,
+                    update_modifier_labels,
+                )
+                    .run_if(in_state(AppState::Menu)),
+            )
+            // Builds the menu again, after the data reset removed it.
+            .add_systems(
+                Update,
+                spawn_main_menu
+                    .run_if(in_state(AppState::Menu))
+                    .run_if(not(any_with_component::<MainMenu>())),
+            )
+            .add_systems(
+                OnExit(AppState::Menu),
+                (submit_unfinished_seed, despawn_main_menu),
+            );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    settings: Res<GameSettings>,
+    progress: Res<Progress>,
+    streak: Res<Streak>,
+    leaderboard: Res<Leaderboard>,
+    next_run_seed: Res<NextRunSeed>,
+    run_modifiers: Res<RunModifiers>,
+) {
+    let font = game_assets.font.clone();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: MENU_STYLE,
+                background_color: MENU_BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            MainMenu,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: PANEL_STYLE,
+                        ..default()
+                    },
+                    MenuPanel::Main,
+                ))
+                .with_children(|parent| {
+                    // === Day streak ===
+                    parent.spawn(TextBundle::from_section(
+                        format!("Day streak: {}", streak.count),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+
+                    // === Play Button ===
+                    parent.spawn((
+                        ButtonBundle {
+                            style: PLAY_BUTTON_STYLE,
+                            image: asset_server.load("art/Play.png").into(),
+                            background_color: NORMAL_BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        PlayButton,
+                    ));
+
+                    // === Skins ===
+                    parent
+                        .spawn(NodeBundle {
+                            style: SKIN_GRID_STYLE,
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for skin in PlayerSkin::ALL {
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: SKIN_BUTTON_STYLE,
+                                            image: asset_server.load(skin.texture_path()).into(),
+                                            background_color: UNSELECTED_SKIN_COLOR.into(),
+                                            ..default()
+                                        },
+                                        SkinButton(skin),
+                                    ))
+                                    .with_children(|parent| {
+                                        if !skin.is_unlocked(&progress) {
+                                            parent.spawn(TextBundle::from_section(
+                                                skin.unlock_hint(),
+                                                TextStyle {
+                                                    font: font.clone(),
+                                                    font_size: 16.0,
+                                                    color: Color::WHITE,
+                                                },
+                                            ));
+                                        }
+                                    });
+                            }
+                        });
+
+                    for panel in [
+                        MenuPanel::Modifiers,
+                        MenuPanel::Settings,
+                        MenuPanel::Leaderboard,
+                    ] {
+                        spawn_menu_button(parent, PanelButton(panel), panel.button_label(), &font);
+                    }
+
+                    spawn_menu_button(parent, WatchReplayButton, "Watch last replay", &font);
+                    spawn_menu_button(
+                        parent,
+                        SeedButton,
+                        &seed_button_label(&next_run_seed, None),
+                        &font,
+                    );
+                });
+
+            let mut hidden_panel_style = PANEL_STYLE;
+            hidden_panel_style.display = Display::None;
+
+            // === Settings ===
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: hidden_panel_style.clone(),
+                        ..default()
+                    },
+                    MenuPanel::Settings,
+                ))
+                .with_children(|parent| {
+                    let pages = SettingButton::ALL.chunks(SETTINGS_PER_PAGE);
+
+                    for (page, setting_buttons) in pages.enumerate() {
+                        let mut settings_grid_style = SETTINGS_GRID_STYLE;
+                        if page > 0 {
+                            settings_grid_style.display = Display::None;
+                        }
+
+                        parent
+                            .spawn((
+                                NodeBundle {
+                                    style: settings_grid_style,
+                                    ..default()
+                                },
+                                SettingsPage(page),
+                            ))
+                            .with_children(|parent| {
+                                for &setting_button in setting_buttons {
+                                    spawn_menu_button(
+                                        parent,
+                                        setting_button,
+                                        &setting_button.label(&settings),
+                                        &font,
+                                    );
+                                }
+                            });
+                    }
+
+                    spawn_menu_button(
+                        parent,
+                        SettingsPageButton { page: 0 },
+                        &settings_page_label(0),
+                        &font,
+                    );
+
+                    spawn_menu_button(
+                        parent,
+                        PanelButton(MenuPanel::ConfirmReset),
+                        MenuPanel::ConfirmReset.button_label(),
+                        &font,
+                    );
+
+                    spawn_back_button(parent, &font);
+                });
+
+            // === Modifiers ===
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: hidden_panel_style.clone(),
+                        ..default()
+                    },
+                    MenuPanel::Modifiers,
+                ))
+                .with_children(|parent| {
+                    for modifier in RunModifier::ALL {
+                        spawn_menu_button(
+                            parent,
+                            ModifierButton(modifier),
+                            &modifier_label(modifier, &run_modifiers),
+                            &font,
+                        );
+                    }
+
+                    spawn_back_button(parent, &font);
+                });
+
+            // === Reset confirmation ===
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: hidden_panel_style.clone(),
+                        ..default()
+                    },
+                    MenuPanel::ConfirmReset,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Delete the progress, settings and leaderboard?",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+
+                    spawn_menu_button(parent, ResetButton, "Yes, reset everything", &font);
+                    spawn_back_button(parent, &font);
+                });
+
+            // === Leaderboard ===
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: hidden_panel_style,
+                        ..default()
+                    },
+                    MenuPanel::Leaderboard,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        leaderboard.to_text(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 28.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+
+                    spawn_back_button(parent, &font);
+                });
+        });
+}
+
+fn spawn_back_button(parent: &mut ChildBuilder, font: &Handle<Font>) {
+    spawn_menu_button(
+        parent,
+        PanelButton(MenuPanel::Main),
+        MenuPanel::Main.button_label(),
+        font,
+    );
+}
+
+fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    button: impl Component,
+    label: &str,
+    font: &Handle<Font>,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: SETTING_BUTTON_STYLE,
+                background_color: NORMAL_SETTING_BUTTON_COLOR.into(),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 28.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn despawn_main_menu(mut commands: Commands, menu_query: Query<Entity, With<MainMenu>>) {
+    for menu_entity in menu_query.iter() {
+        commands.entity(menu_entity).despawn_recursive();
+    }
+}
+
+fn interact_with_play_button(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<PlayButton>),
+    >,
+    mut app_state_next_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                app_state_next_state.set(AppState::Playing);
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+// Shows the menu page of the pressed button.
+fn interact_with_panel_buttons(
+    mut button_query: Query<
+        (&Interaction, &PanelButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut panel_query: Query<(&MenuPanel, &mut Style)>,
+) {
+    for (interaction, panel_button, mut background_color) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+
+                for (panel, mut panel_style) in panel_query.iter_mut() {
+                    panel_style.display = if *panel == panel_button.0 {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    };
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+fn interact_with_reset_button(
+    mut commands: Commands,
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ResetButton>),
+    >,
+    menu_query: Query<Entity, With<MainMenu>>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+                save::reset_all_data(&mut commands);
+
+                // The menu is spawned again from the reset data on the next frame.
+                for menu_entity in menu_query.iter() {
+                    commands.entity(menu_entity).despawn_recursive();
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn interact_with_watch_replay_button(
+    mut commands: Commands,
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<WatchReplayButton>),
+    >,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    mut settings: ResMut<GameSettings>,
+    mut run_modifiers: ResMut<RunModifiers>,
+    mut app_state_next_state: ResMut<NextState<AppState>>,
+    game_balance: Res<GameBalance>,
+    planet_definitions: Res<PlanetDefinitions>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+
+                if replay::start_playback(
+                    &mut commands,
+                    &mut time_update_strategy,
+                    &mut settings,
+                    &mut run_modifiers,
+                    &game_balance,
+                    &planet_definitions,
+                ) {
+                    app_state_next_state.set(AppState::Playing);
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+fn seed_button_label(next_run_seed: &NextRunSeed, seed_entry: Option<&SeedEntry>) -> String {
+    match (seed_entry, next_run_seed.0) {
+        (Some(seed_entry), _) => format!("Seed: {}_", seed_entry.digits),
+        (None, Some(seed)) => format!("Seed: {}", seed),
+        (None, None) => "Seed: Random".to_string(),
+    }
+}
+
+fn interact_with_seed_button(
+    mut commands: Commands,
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SeedButton>),
+    >,
+    seed_entry: Option<Res<SeedEntry>>,
+    mut next_run_seed: ResMut<NextRunSeed>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+
+                match seed_entry {
+                    Some(seed_entry) => {
+                        submit_seed(&mut commands, &seed_entry, &mut next_run_seed);
+                    }
+                    None => commands.insert_resource(SeedEntry {
+                        digits: next_run_seed
+                            .0
+                            .map_or(String::new(), |seed| seed.to_string()),
+                    }),
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+// Digits only, Backspace to erase, Enter to confirm. An empty seed is random.
+fn type_seed(
+    mut commands: Commands,
+    mut received_character_event_reader: EventReader<ReceivedCharacter>,
+    seed_entry: Option<ResMut<SeedEntry>>,
+    mut next_run_seed: ResMut<NextRunSeed>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    let Some(mut seed_entry) = seed_entry else {
+        received_character_event_reader.clear();
+        return;
+    };
+
+    for received_character in received_character_event_reader.iter() {
+        let character = received_character.char;
+
+        if character.is_ascii_digit() && seed_entry.digits.len() < SEED_MAX_DIGITS {
+            seed_entry.digits.push(character);
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Back) {
+        seed_entry.digits.pop();
+    }
+
+    if keyboard.just_pressed(KeyCode::Return) {
+        submit_seed(&mut commands, &seed_entry, &mut next_run_seed);
+    }
+}
+
+fn submit_seed(commands: &mut Commands, seed_entry: &SeedEntry, next_run_seed: &mut NextRunSeed) {
+    if seed_entry.digits.is_empty() {
+        next_run_seed.0 = None;
+    } else {
+        match seed_entry.digits.parse::<u64>() {
+            Ok(seed) => next_run_seed.0 = Some(seed),
+            Err(_) => {
+                warn!(
+                    "Seed {} is too large, keeping the last one",
+                    seed_entry.digits
+                );
+            }
+        }
+    }
+
+    commands.remove_resource::<SeedEntry>();
+}
+
+// Keeps the typed seed, if the run starts before it's confirmed.
+fn submit_unfinished_seed(
+    mut commands: Commands,
+    seed_entry: Option<Res<SeedEntry>>,
+    mut next_run_seed: ResMut<NextRunSeed>,
+) {
+    if let Some(seed_entry) = seed_entry {
+        submit_seed(&mut commands, &seed_entry, &mut next_run_seed);
+    }
+}
+
+fn update_seed_label(
+    seed_button_query: Query<&Children, With<SeedButton>>,
+    mut text_query: Query<&mut Text>,
+    seed_entry: Option<Res<SeedEntry>>,
+    next_run_seed: Res<NextRunSeed>,
+    mut was_typing: Local<bool>,
+) {
+    let is_typing = seed_entry.is_some();
+    let is_entry_changed = seed_entry
+        .as_ref()
+        .is_some_and(|seed_entry| seed_entry.is_changed());
+
+    if !next_run_seed.is_changed() && !is_entry_changed && is_typing == *was_typing {
+        return;
+    }
+    *was_typing = is_typing;
+
+    for children in seed_button_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = seed_button_label(&next_run_seed, seed_entry.as_deref());
+            }
+        }
+    }
+}
+
+fn interact_with_setting_buttons(
+    mut button_query: Query<
+        (&Interaction, &SettingButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut settings: ResMut<GameSettings>,
+) {
+    for (interaction, setting_button, mut background_color) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+                setting_button.cycle(&mut settings);
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+// Selects the pressed skin, if it is unlocked, and highlights the selected one.
+fn interact_with_skin_buttons(
+    mut button_query: Query<(&Interaction, &SkinButton, &mut BackgroundColor)>,
+    mut settings: ResMut<GameSettings>,
+    progress: Res<Progress>,
+) {
+    for (interaction, skin_button, mut background_color) in button_query.iter_mut() {
+        let skin = skin_button.0;
+
+        if !skin.is_unlocked(&progress) {
+            *background_color = LOCKED_SKIN_COLOR.into();
+            continue;
+        }
+
+        if *interaction == Interaction::Pressed && settings.player_skin != skin {
+            settings.player_skin = skin;
+        }
+
+        *background_color = if settings.player_skin == skin {
+            NORMAL_BUTTON_COLOR.into()
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_BUTTON_COLOR.into()
+        } else {
+            UNSELECTED_SKIN_COLOR.into()
+        };
+    }
+}
+
+// Updates the setting button labels, if some of the settings changed.
+fn interact_with_settings_page_button(
+    mut button_query: Query<
+        (
+            &Interaction,
+            &mut SettingsPageButton,
+            &mut BackgroundColor,
+            &Children,
+        ),
+        Changed<Interaction>,
+    >,
+    mut page_query: Query<(&SettingsPage, &mut Style)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let Ok((interaction, mut page_button, mut background_color, children)) =
+        button_query.get_single_mut()
+    else {
+        return;
+    };
+
+    match *interaction {
+        Interaction::Pressed => {
+            *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+        }
+        Interaction::Hovered => {
+            *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            return;
+        }
+        Interaction::None => {
+            *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            return;
+        }
+    }
+
+    page_button.page = (page_button.page + 1) % settings_page_count();
+
+    for (settings_page, mut page_style) in page_query.iter_mut() {
+        page_style.display = if settings_page.0 == page_button.page {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    for &child in children.iter() {
+        if let Ok(mut text) = text_query.get_mut(child) {
+            text.sections[0].value = settings_page_label(page_button.page);
+        }
+    }
+}
+
+fn update_setting_labels(
+    setting_button_query: Query<(&SettingButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+    settings: Res<GameSettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (setting_button, children) in setting_button_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = setting_button.label(&settings);
+            }
+        }
+    }
+}
+
+fn interact_with_modifier_buttons(
+    mut button_query: Query<
+        (&Interaction, &ModifierButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut run_modifiers: ResMut<RunModifiers>,
+) {
+    for (interaction, modifier_button, mut background_color) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+                run_modifiers.toggle(modifier_button.0);
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+fn update_modifier_labels(
+    modifier_button_query: Query<(&ModifierButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+    run_modifiers: Res<RunModifiers>,
+) {
+    if !run_modifiers.is_changed() {
+        return;
+    }
+
+    for (modifier_button, children) in modifier_button_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = modifier_label(modifier_button.0, &run_modifiers);
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::planets::PlanetDef;
+
+/// Obstacle speed and points multiplier of `RunModifier::FastObstacles`.
+pub const FAST_OBSTACLES_SCALE: usize = 2;
+/// Size of the planets with `RunModifier::TinyPlanets`, relative to their definition.
+/// Has to keep them above the smallest shrink limit.
+pub const TINY_PLANET_SCALE: f32 = 0.7;
+
+/// Optional twist of a run, picked in the menu before it starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RunModifier {
+    /// Obstacles move twice as fast, every point counts twice.
+    FastObstacles,
+    /// Planets spawn smaller, so there's less time on each.
+    TinyPlanets,
+    NoFastFall,
+    /// Planets turn the other way.
+    ReversedRotation,
+}
+
+impl RunModifier {
+    pub const ALL: [RunModifier; 4] = [
+        RunModifier::FastObstacles,
+        RunModifier::TinyPlanets,
+        RunModifier::NoFastFall,
+        RunModifier::ReversedRotation,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RunModifier::FastObstacles => "Fast obstacles, double points",
+            RunModifier::TinyPlanets => "Tiny planets",
+            RunModifier::NoFastFall => "No fast-fall",
+            RunModifier::ReversedRotation => "Reversed rotation",
+        }
+    }
+}
+
+/// Modifiers of the next run, in the order they were picked. Kept between runs,
+/// until they're changed in the menu.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RunModifiers(pub Vec<RunModifier>);
+
+impl RunModifiers {
+    pub fn contains(&self, modifier: RunModifier) -> bool {
+        self.0.contains(&modifier)
+    }
+
+    pub fn toggle(&mut self, modifier: RunModifier) {
+        if self.contains(modifier) {
+            self.0.retain(|&other| other != modifier);
+        } else {
+            self.0.push(modifier);
+        }
+    }
+
+    pub fn obstacle_speed_scale(&self) -> f32 {
+        if self.contains(RunModifier::FastObstacles) {
+            FAST_OBSTACLES_SCALE as f32
+        } else {
+            1.
+        }
+    }
+
+    pub fn score_multiplier(&self) -> usize {
+        if self.contains(RunModifier::FastObstacles) {
+            FAST_OBSTACLES_SCALE
+        } else {
+            1
+        }
+    }
+
+    /// Diameter of the planet when it spawns.
+    pub fn planet_size(&self, planet_def: &PlanetDef) -> Vec2 {
+        if self.contains(RunModifier::TinyPlanets) {
+            planet_def.size() * TINY_PLANET_SCALE
+        } else {
+            planet_def.size()
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        if self.0.is_empty() {
+            return "None".to_string();
+        }
+
+        self.0
+            .iter()
+            .map(|modifier| modifier.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
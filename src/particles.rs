@@ -0,0 +1,84 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::pause::PauseState;
+
+pub const PARTICLE_SIZE: Vec2 = Vec2::new(8., 8.);
+pub const PARTICLE_LIFETIME: f32 = 0.5;
+// Fraction of the speed lost every second.
+pub const PARTICLE_DRAG: f32 = 3.;
+
+/// Short-lived square flying out of a burst, fading and shrinking as it goes.
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        // Keeps going on the game over screen, so the last burst plays out.
+        app.add_systems(
+            Update,
+            update_particles.run_if(in_state(PauseState::Running)),
+        );
+    }
+}
+
+/// Spawns `count` particles flying out of `position` in random directions,
+/// with a speed in the `speed` range.
+pub fn spawn_particle_burst(
+    commands: &mut Commands,
+    position: Vec3,
+    color: Color,
+    count: usize,
+    speed: (f32, f32),
+) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..count {
+        let direction = Vec2::from_angle(rng.gen_range(0. ..2. * PI));
+
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(position),
+                sprite: Sprite {
+                    custom_size: Some(PARTICLE_SIZE),
+                    color,
+                    ..default()
+                },
+                ..default()
+            },
+            Particle {
+                velocity: direction * rng.gen_range(speed.0..speed.1),
+                lifetime: Timer::from_seconds(PARTICLE_LIFETIME, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    mut particle_query: Query<(Entity, &mut Transform, &mut Sprite, &mut Particle)>,
+    time: Res<Time>,
+) {
+    for (particle_entity, mut particle_transform, mut particle_sprite, mut particle) in
+        particle_query.iter_mut()
+    {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(particle_entity).despawn_recursive();
+            continue;
+        }
+
+        particle_transform.translation += (particle.velocity * time.delta_seconds()).extend(0.);
+        particle.velocity *= (1. - PARTICLE_DRAG * time.delta_seconds()).max(0.);
+
+        let life_left = particle.lifetime.percent_left();
+        particle_sprite.color.set_a(life_left);
+        particle_transform.scale = Vec3::splat(life_left);
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::{prelude::*, window::WindowFocused};
+use bevy_tweening::{Animator, AnimatorState};
+
+use crate::{assets::GameAssets, settings::GameSettings, AppState};
+
+pub const PAUSE_KEY: KeyCode = KeyCode::Escape;
+/// Ends a freeplay run from the pause menu, nothing else ends it.
+pub const END_RUN_KEY: KeyCode = KeyCode::Return;
+
+pub const PAUSE_MENU_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.flex_direction = FlexDirection::Column;
+    style.justify_content = JustifyContent::Center;
+    style.align_items = AlignItems::Center;
+    style.width = Val::Percent(100.);
+    style.height = Val::Percent(100.);
+    style
+};
+
+pub const PAUSE_BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.5);
+
+/// Gameplay is frozen while `Paused`. Kept apart from `AppState`,
+/// so resuming doesn't run the `OnEnter(AppState::Playing)` systems again.
+#[derive(States, Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+#[derive(Component)]
+pub struct PauseMenu;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            .add_systems(
+                Update,
+                (toggle_pause, pause_on_focus_loss).run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                end_freeplay_run
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(in_state(PauseState::Paused)),
+            )
+            .add_systems(
+                OnEnter(PauseState::Paused),
+                (spawn_pause_menu, pause_animators),
+            )
+            .add_systems(
+                OnExit(PauseState::Paused),
+                (despawn_pause_menu, resume_animators),
+            )
+            .add_systems(OnExit(AppState::Playing), resume);
+    }
+}
+
+fn toggle_pause(
+    keyboard: Res<Input<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if !keyboard.just_pressed(PAUSE_KEY) {
+        return;
+    }
+
+    next_pause_state.set(match pause_state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+fn end_freeplay_run(
+    keyboard: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if settings.freeplay && keyboard.just_pressed(END_RUN_KEY) {
+        next_app_state.set(AppState::GameOver);
+    }
+}
+
+// Pauses when the player switches to another window. Stays paused after the focus comes back,
+// until the player resumes.
+fn pause_on_focus_loss(
+    mut window_focused_event_reader: EventReader<WindowFocused>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    settings: Res<GameSettings>,
+) {
+    let lost_focus = window_focused_event_reader
+        .iter()
+        .any(|window_focused| !window_focused.focused);
+
+    if lost_focus && settings.pause_on_focus_loss {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+fn resume(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    next_pause_state.set(PauseState::Running);
+}
+
+// Holds the camera and background transitions, so they don't finish while paused.
+fn pause_animators(mut animator_query: Query<&mut Animator<Transform>>) {
+    for mut animator in animator_query.iter_mut() {
+        animator.state = AnimatorState::Paused;
+    }
+}
+
+fn resume_animators(mut animator_query: Query<&mut Animator<Transform>>) {
+    for mut animator in animator_query.iter_mut() {
+        animator.state = AnimatorState::Playing;
+    }
+}
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    settings: Res<GameSettings>,
+) {
+    let hint = if settings.freeplay {
+        "Press Esc to resume, Enter to end the run"
+    } else {
+        "Press Esc to resume"
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: PAUSE_MENU_STYLE,
+                background_color: PAUSE_BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            PauseMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 96.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                hint,
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn despawn_pause_menu(mut commands: Commands, pause_menu_query: Query<Entity, With<PauseMenu>>) {
+    for pause_menu_entity in pause_menu_query.iter() {
+        commands.entity(pause_menu_entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::PlanetVariant;
+
+/// Planet layouts in the asset folder, read at startup. Planets missing from the file
+/// keep the built-in definition.
+pub const PLANET_DEFINITIONS_PATH: &str = "planets.ron";
+/// The shipped planets.ron, baked in as the built-in definitions.
+const BUILTIN_PLANET_DEFINITIONS: &str = include_str!("../assets/planets.ron");
+
+/// Everything that sets a planet apart. Angles are in degrees, counter-clockwise from the right.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlanetDef {
+    pub texture: String,
+    /// Diameter of the planet when it spawns.
+    pub size: f32,
+    pub obstacle_texture: String,
+    /// Whether the obstacle sprites turn with the orbit.
+    pub obstacle_rotates: bool,
+    /// Frames of the obstacle animation, laid out left to right in the obstacle texture.
+    #[serde(default = "single_frame")]
+    pub obstacle_frames: usize,
+    /// Arc (from, to), where random obstacles can appear in infinite mode.
+    /// Should stay inside of the `OBSTACLE_FULL_ARC`, so the player can always land.
+    pub obstacle_arc: (f32, f32),
+    /// Obstacle angles of the story mode. The planet shrinks in steps, one per wave,
+    /// and spawns the pattern of the next wave after each.
+    pub waves: Vec<Vec<f32>>,
+}
+
+impl PlanetDef {
+    pub fn size(&self) -> Vec2 {
+        Vec2::splat(self.size)
+    }
+
+    /// Arc of the random obstacles in radians.
+    pub fn obstacle_arc_radians(&self) -> (f32, f32) {
+        (
+            self.obstacle_arc.0.to_radians(),
+            self.obstacle_arc.1.to_radians(),
+        )
+    }
+
+    /// Story obstacle angles of the wave in radians. Waves past the last repeat it.
+    pub fn wave_obstacles(&self, wave: usize) -> Vec<f32> {
+        self.waves
+            .get(wave)
+            .or(self.waves.last())
+            .map(|angles| angles.iter().map(|angle| angle.to_radians()).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn single_frame() -> usize {
+    1
+}
+
+/// Handles of every planet and obstacle texture, loaded at startup and kept alive,
+/// so the incoming planet and its obstacles show up right away during the camera pan.
+#[derive(Resource, Default)]
+pub struct PlanetTextures {
+    _handles: Vec<Handle<Image>>,
+}
+
+/// Definitions of every planet, keyed by variant.
+#[derive(Resource)]
+pub struct PlanetDefinitions(HashMap<PlanetVariant, PlanetDef>);
+
+impl PlanetDefinitions {
+    pub fn get(&self, variant: PlanetVariant) -> &PlanetDef {
+        &self.0[&variant]
+    }
+}
+
+impl Default for PlanetDefinitions {
+    fn default() -> Self {
+        PlanetDefinitions(
+            ron::from_str(BUILTIN_PLANET_DEFINITIONS)
+                .expect("The built-in planets.ron has to define every planet"),
+        )
+    }
+}
+
+pub struct PlanetsPlugin;
+
+impl Plugin for PlanetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_planet_definitions())
+            .add_systems(Startup, preload_planet_textures);
+    }
+}
+
+fn preload_planet_textures(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    planet_definitions: Res<PlanetDefinitions>,
+) {
+    let handles = planet_definitions
+        .0
+        .values()
+        .flat_map(|planet_def| [&planet_def.texture, &planet_def.obstacle_texture])
+        .map(|path| asset_server.load(path.as_str()))
+        .collect();
+
+    commands.insert_resource(PlanetTextures { _handles: handles });
+}
+
+/// Reads `PLANET_DEFINITIONS_PATH` on top of the built-in definitions.
+pub fn load_planet_definitions() -> PlanetDefinitions {
+    let mut planet_definitions = PlanetDefinitions::default();
+
+    let Some(data) = read_planet_definitions() else {
+        info!("No {}, using the built-in planets", PLANET_DEFINITIONS_PATH);
+        return planet_definitions;
+    };
+
+    match ron::from_str::<HashMap<PlanetVariant, PlanetDef>>(&data) {
+        Ok(loaded_definitions) => {
+            for (variant, planet_def) in loaded_definitions {
+                if planet_def.waves.is_empty() || planet_def.obstacle_frames == 0 {
+                    warn!(
+                        "{} has no obstacle waves or frames, using the built-in planet",
+                        variant.name()
+                    );
+                    continue;
+                }
+
+                planet_definitions.0.insert(variant, planet_def);
+            }
+        }
+        Err(error) => {
+            warn!(
+                "Failed to parse {}, using the built-in planets: {}",
+                PLANET_DEFINITIONS_PATH, error
+            );
+        }
+    }
+
+    planet_definitions
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_planet_definitions() -> Option<String> {
+    std::fs::read_to_string(crate::assets::asset_path(PLANET_DEFINITIONS_PATH)).ok()
+}
+
+// The web build has no file system, the planets are baked in.
+#[cfg(target_arch = "wasm32")]
+fn read_planet_definitions() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_planets_define_every_variant() {
+        let planet_definitions = PlanetDefinitions::default();
+
+        for variant in PlanetVariant::ALL {
+            let planet_def = planet_definitions.0.get(&variant);
+            assert!(planet_def.is_some(), "{} is missing", variant.name());
+            assert!(!planet_def.unwrap().waves.is_empty());
+        }
+    }
+}
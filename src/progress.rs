@@ -0,0 +1,159 @@
+use bevy::{app::AppExit, prelude::*, window::WindowCloseRequested};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    replay::ReplayPlayback,
+    save,
+    settings::{write_settings, GameSettings},
+    stats::RunStats,
+    AppState, GameManager, RunConfig,
+};
+
+pub const PROGRESS_SAVE_KEY: &str = "progress";
+pub const STREAK_SAVE_KEY: &str = "streak";
+
+/// Player achievements across all runs, saved after every run.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    pub best_score: usize,
+    pub total_planets_cleared: usize,
+    pub total_obstacles_dodged: usize,
+    /// Longest run in seconds.
+    pub longest_survival: f32,
+}
+
+impl Progress {
+    pub fn record_run(&mut self, game_manager: &GameManager, run_stats: &RunStats) {
+        self.best_score = self.best_score.max(game_manager.score);
+        self.total_planets_cleared += game_manager.planets_cleared;
+        self.total_obstacles_dodged += run_stats.obstacles_dodged;
+        self.longest_survival = self.longest_survival.max(run_stats.survival_time);
+    }
+}
+
+/// Number of consecutive days the game was launched.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Streak {
+    pub count: usize,
+    pub last_date: Option<NaiveDate>,
+}
+
+impl Streak {
+    /// Continues the streak if the game was launched yesterday, otherwise starts a new one.
+    pub fn record_launch(&mut self, today: NaiveDate) {
+        match self.last_date {
+            Some(last_date) if last_date == today => {}
+            Some(last_date) if last_date.succ_opt() == Some(today) => {
+                self.count += 1;
+            }
+            _ => {
+                self.count = 1;
+            }
+        }
+
+        self.last_date = Some(today);
+    }
+}
+
+pub struct ProgressPlugin;
+
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        // Days are compared in the local time zone of the player.
+        let mut streak = save::load::<Streak>(STREAK_SAVE_KEY).unwrap_or_default();
+        streak.record_launch(Local::now().date_naive());
+        save::save(STREAK_SAVE_KEY, &streak);
+
+        save::install_unload_hook();
+
+        app.insert_resource(save::load::<Progress>(PROGRESS_SAVE_KEY).unwrap_or_default())
+            .insert_resource(streak)
+            .add_systems(OnEnter(AppState::GameOver), record_run)
+            .add_systems(OnEnter(AppState::Victory), record_run)
+            .add_systems(
+                Update,
+                stage_run_for_unload.run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Last, save_before_exit);
+    }
+}
+
+/// Replays were counted when they were played, freeplay runs can't be lost
+/// and a debug starting score isn't earned.
+fn is_run_counted(settings: &GameSettings, run_config: &RunConfig, is_playback: bool) -> bool {
+    !is_playback && !settings.freeplay && run_config.starting_score == 0
+}
+
+fn record_run(
+    mut progress: ResMut<Progress>,
+    game_manager: Res<GameManager>,
+    run_stats: Res<RunStats>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if !is_run_counted(&settings, &run_config, playback.is_some()) {
+        return;
+    }
+
+    progress.record_run(&game_manager, &run_stats);
+
+    save::save(PROGRESS_SAVE_KEY, &*progress);
+}
+
+// Keeps the progress with the current run ready, in case the page is closed mid-run.
+fn stage_run_for_unload(
+    progress: Res<Progress>,
+    game_manager: Res<GameManager>,
+    run_stats: Res<RunStats>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if game_manager.is_changed() && is_run_counted(&settings, &run_config, playback.is_some()) {
+        let mut progress_with_run = progress.clone();
+        progress_with_run.record_run(&game_manager, &run_stats);
+
+        save::save_on_unload(PROGRESS_SAVE_KEY, &progress_with_run);
+    }
+}
+
+// Flushes the progress, counting the unfinished run, and the settings when the game closes.
+#[allow(clippy::too_many_arguments)]
+fn save_before_exit(
+    mut app_exit_event_reader: EventReader<AppExit>,
+    mut window_close_event_reader: EventReader<WindowCloseRequested>,
+    mut progress: ResMut<Progress>,
+    game_manager: Res<GameManager>,
+    run_stats: Res<RunStats>,
+    app_state: Res<State<AppState>>,
+    settings: Res<GameSettings>,
+    run_config: Res<RunConfig>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut has_saved: Local<bool>,
+) {
+    let is_exiting = app_exit_event_reader.iter().next().is_some()
+        | window_close_event_reader.iter().next().is_some();
+
+    // Both events may come on different frames, count the run only once.
+    if !is_exiting || *has_saved {
+        return;
+    }
+    *has_saved = true;
+
+    if let Some(playback) = playback {
+        save::save(PROGRESS_SAVE_KEY, &*progress);
+        write_settings(&playback.original_settings);
+        return;
+    }
+
+    if *app_state.get() == AppState::Playing && is_run_counted(&settings, &run_config, false) {
+        progress.record_run(&game_manager, &run_stats);
+    }
+
+    save::save(PROGRESS_SAVE_KEY, &*progress);
+    write_settings(&settings);
+}
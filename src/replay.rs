@@ -0,0 +1,438 @@
+use std::time::Duration;
+
+use bevy::{input::InputSystem, prelude::*, time::TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    balance::GameBalance, game_time::TimeScale, modifiers::RunModifiers, pause::PauseState,
+    planets::PlanetDefinitions, save, settings::GameSettings, start_game, AppState, GameManager,
+    GameRng, HitStop, LoadingState, PlanetVariant, RunConfig,
+};
+
+pub const REPLAY_SAVE_KEY: &str = "replay";
+/// Bump when the gameplay changes in a way that makes older replays play out differently.
+pub const REPLAY_VERSION: u32 = 8;
+
+const JUMP_KEY_BIT: u8 = 1;
+const FAST_FALL_KEY_BIT: u8 = 1 << 1;
+const DASH_KEY_BIT: u8 = 1 << 2;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Frame time and the held keys of one gameplay frame.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub delta_nanos: u32,
+    pub keys: u8,
+}
+
+/// Everything needed to play a run again: the seed of the `GameRng`, the settings,
+/// the run modifiers and the inputs of every frame. The result is kept to check, that the playback matches.
+/// The balance and the planet definitions are only hashed, a replay is refused, if they changed since.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub settings: GameSettings,
+    /// Replays from before the modifiers ran without any.
+    #[serde(default)]
+    pub modifiers: RunModifiers,
+    pub balance_hash: u64,
+    pub planets_hash: u64,
+    /// Keys held while the first planet loaded, pressed until the recorded frames start.
+    pub start_keys: u8,
+    pub frames: Vec<ReplayFrame>,
+    pub score: usize,
+    pub planets_cleared: usize,
+}
+
+/// The replay of the current run, saved when it ends.
+#[derive(Resource)]
+pub struct ReplayRecorder(pub Replay);
+
+/// The run being played back from a replay instead of the keyboard.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub frame: usize,
+    /// Settings and modifiers of the player, restored when the playback ends.
+    pub original_settings: GameSettings,
+    pub original_modifiers: RunModifiers,
+}
+
+/// Set once the first planet of the run has loaded. The load takes a different number
+/// of frames every time, so the recorded frames only start after it.
+#[derive(Resource, Default)]
+struct RunLoaded(bool);
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunLoaded>()
+            .add_systems(
+                OnEnter(AppState::Playing),
+                (reset_run_loaded, seed_playback, start_recording)
+                    .chain()
+                    .after(start_game),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    mark_run_loaded,
+                    press_replay_keys.run_if(resource_exists::<ReplayPlayback>()),
+                )
+                    .chain()
+                    .after(InputSystem)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    record_frame.run_if(resource_exists::<ReplayRecorder>()),
+                    advance_playback
+                        .run_if(resource_exists::<ReplayPlayback>())
+                        .run_if(is_run_loaded),
+                )
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not(resource_exists::<HitStop>())),
+            )
+            .add_systems(
+                Last,
+                schedule_playback_delta
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(resource_exists::<ReplayPlayback>()),
+            )
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                (save_recorded_replay, check_playback),
+            )
+            .add_systems(
+                OnEnter(AppState::Victory),
+                (save_recorded_replay, check_playback),
+            )
+            .add_systems(OnExit(AppState::GameOver), end_playback)
+            .add_systems(OnExit(AppState::Victory), end_playback);
+    }
+}
+
+/// FNV-1a hash of the RON text of the `value`, stable between builds and platforms.
+fn ron_hash<T: Serialize>(value: &T) -> u64 {
+    let data = ron::to_string(value).unwrap_or_default();
+
+    data.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+pub fn balance_hash(game_balance: &GameBalance) -> u64 {
+    ron_hash(game_balance)
+}
+
+/// Hashes the definitions in the order of `PlanetVariant::ALL`, the map order isn't stable.
+pub fn planets_hash(planet_definitions: &PlanetDefinitions) -> u64 {
+    let planet_defs: Vec<_> = PlanetVariant::ALL
+        .into_iter()
+        .map(|variant| planet_definitions.get(variant))
+        .collect();
+
+    ron_hash(&planet_defs)
+}
+
+/// Loads the last saved replay and starts playing it back with its settings and modifiers.
+/// Returns `false`, if there's no replay, it's from another version of the game,
+/// or it was recorded with another balance or other planets.
+pub fn start_playback(
+    commands: &mut Commands,
+    time_update_strategy: &mut TimeUpdateStrategy,
+    settings: &mut GameSettings,
+    run_modifiers: &mut RunModifiers,
+    game_balance: &GameBalance,
+    planet_definitions: &PlanetDefinitions,
+) -> bool {
+    let Some(replay) = save::load::<Replay>(REPLAY_SAVE_KEY) else {
+        warn!("No replay to play back");
+        return false;
+    };
+
+    if replay.version != REPLAY_VERSION {
+        warn!(
+            "The replay is from version {}, only version {} can be played back",
+            replay.version, REPLAY_VERSION
+        );
+        return false;
+    }
+
+    if replay.balance_hash != balance_hash(game_balance) {
+        warn!("The replay was recorded with another balance, it can't be played back");
+        return false;
+    }
+
+    if replay.planets_hash != planets_hash(planet_definitions) {
+        warn!("The replay was recorded with other planets, it can't be played back");
+        return false;
+    }
+
+    let Some(first_frame) = replay.frames.first() else {
+        warn!("The replay has no frames");
+        return false;
+    };
+
+    // The first frame of the run already needs the recorded frame time.
+    *time_update_strategy =
+        TimeUpdateStrategy::ManualDuration(Duration::from_nanos(first_frame.delta_nanos as u64));
+
+    let original_settings = std::mem::replace(settings, replay.settings.clone());
+    let original_modifiers = std::mem::replace(run_modifiers, replay.modifiers.clone());
+
+    commands.insert_resource(ReplayPlayback {
+        replay,
+        frame: 0,
+        original_settings,
+        original_modifiers,
+    });
+
+    true
+}
+
+fn seed_playback(mut game_rng: ResMut<GameRng>, playback: Option<Res<ReplayPlayback>>) {
+    if let Some(playback) = playback {
+        *game_rng = GameRng::from_seed(playback.replay.seed);
+    }
+}
+
+fn start_recording(
+    mut commands: Commands,
+    game_rng: Res<GameRng>,
+    settings: Res<GameSettings>,
+    run_modifiers: Res<RunModifiers>,
+    run_config: Res<RunConfig>,
+    game_balance: Res<GameBalance>,
+    planet_definitions: Res<PlanetDefinitions>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    // Replays always start from zero, a debug starting score can't be played back.
+    if playback.is_some() || run_config.starting_score > 0 {
+        commands.remove_resource::<ReplayRecorder>();
+        return;
+    }
+
+    commands.insert_resource(ReplayRecorder(Replay {
+        version: REPLAY_VERSION,
+        seed: game_rng.seed,
+        settings: settings.clone(),
+        modifiers: run_modifiers.clone(),
+        balance_hash: balance_hash(&game_balance),
+        planets_hash: planets_hash(&planet_definitions),
+        start_keys: 0,
+        frames: Vec::new(),
+        score: 0,
+        planets_cleared: 0,
+    }));
+}
+
+fn reset_run_loaded(mut run_loaded: ResMut<RunLoaded>) {
+    run_loaded.0 = false;
+}
+
+// Runs before the state transitions, so it sees the first planet about to finish loading
+// and both the recording and the playback start on the same frame.
+fn mark_run_loaded(
+    mut run_loaded: ResMut<RunLoaded>,
+    loading_state: Res<State<LoadingState>>,
+    next_loading_state: Res<NextState<LoadingState>>,
+) {
+    if *loading_state.get() == LoadingState::Planet
+        && next_loading_state.0 == Some(LoadingState::None)
+    {
+        run_loaded.0 = true;
+    }
+}
+
+fn is_run_loaded(run_loaded: Res<RunLoaded>) -> bool {
+    run_loaded.0
+}
+
+fn record_frame(
+    mut commands: Commands,
+    mut recorder: ResMut<ReplayRecorder>,
+    run_loaded: Res<RunLoaded>,
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<GameSettings>,
+    time_scale: Res<TimeScale>,
+    time: Res<Time>,
+) {
+    // The playback runs at full speed, a slowed down run can't be played back.
+    if time_scale.0 != 1. {
+        info!("The time scale changed, the run isn't recorded");
+        commands.remove_resource::<ReplayRecorder>();
+        return;
+    }
+
+    let key_bindings = &settings.key_bindings;
+    let mut keys = 0;
+
+    for (key, bit) in [
+        (key_bindings.jump, JUMP_KEY_BIT),
+        (key_bindings.fast_fall, FAST_FALL_KEY_BIT),
+        (key_bindings.dash, DASH_KEY_BIT),
+    ] {
+        if keyboard_input.pressed(key) {
+            keys |= bit;
+        }
+    }
+
+    // The keys held on the last frame before the start decide, whether the first frame presses them.
+    if !run_loaded.0 {
+        recorder.0.start_keys = keys;
+        return;
+    }
+
+    recorder.0.frames.push(ReplayFrame {
+        delta_nanos: time.delta().as_nanos() as u32,
+        keys,
+    });
+}
+
+// Holds the recorded keys, so the gameplay systems read them like the keyboard.
+fn press_replay_keys(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    playback: Res<ReplayPlayback>,
+    run_loaded: Res<RunLoaded>,
+    settings: Res<GameSettings>,
+) {
+    let keys = if run_loaded.0 {
+        let Some(frame) = playback.replay.frames.get(playback.frame) else {
+            return;
+        };
+
+        frame.keys
+    } else {
+        playback.replay.start_keys
+    };
+
+    let key_bindings = &settings.key_bindings;
+
+    for (key, bit) in [
+        (key_bindings.jump, JUMP_KEY_BIT),
+        (key_bindings.fast_fall, FAST_FALL_KEY_BIT),
+        (key_bindings.dash, DASH_KEY_BIT),
+    ] {
+        if keys & bit != 0 {
+            keyboard_input.press(key);
+        } else {
+            keyboard_input.release(key);
+        }
+    }
+}
+
+// Ends the run, if the recorded frames ran out before it ended on its own.
+fn advance_playback(
+    mut playback: ResMut<ReplayPlayback>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    playback.frame += 1;
+
+    if playback.frame >= playback.replay.frames.len() {
+        warn!("The replay ended before the run, it went out of sync");
+        next_app_state.set(AppState::GameOver);
+    }
+}
+
+// Until the first planet has loaded, the frames keep the time of the first recorded frame.
+fn schedule_playback_delta(
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    playback: Res<ReplayPlayback>,
+) {
+    *time_update_strategy = match playback.replay.frames.get(playback.frame) {
+        Some(frame) => {
+            TimeUpdateStrategy::ManualDuration(Duration::from_nanos(frame.delta_nanos as u64))
+        }
+        None => TimeUpdateStrategy::Automatic,
+    };
+}
+
+fn save_recorded_replay(
+    mut commands: Commands,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    game_manager: Res<GameManager>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+
+    recorder.0.score = game_manager.score;
+    recorder.0.planets_cleared = game_manager.planets_cleared;
+    save::save(REPLAY_SAVE_KEY, &recorder.0);
+
+    commands.remove_resource::<ReplayRecorder>();
+}
+
+// Compares the result of the playback with the recorded run.
+fn check_playback(
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    playback: Option<Res<ReplayPlayback>>,
+    game_manager: Res<GameManager>,
+) {
+    let Some(playback) = playback else {
+        return;
+    };
+
+    *time_update_strategy = TimeUpdateStrategy::Automatic;
+
+    let replay = &playback.replay;
+    if game_manager.score == replay.score && game_manager.planets_cleared == replay.planets_cleared
+    {
+        info!("Replay of seed {} matched the recorded run", replay.seed);
+    } else {
+        warn!(
+            "Replay of seed {} went out of sync: score {} instead of {}, {} planets instead of {}",
+            replay.seed,
+            game_manager.score,
+            replay.score,
+            game_manager.planets_cleared,
+            replay.planets_cleared
+        );
+    }
+}
+
+fn end_playback(
+    mut commands: Commands,
+    mut settings: ResMut<GameSettings>,
+    mut run_modifiers: ResMut<RunModifiers>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if let Some(playback) = playback {
+        *settings = playback.original_settings.clone();
+        *run_modifiers = playback.original_modifiers.clone();
+        commands.remove_resource::<ReplayPlayback>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_only_change_with_the_content() {
+        let game_balance = GameBalance::default();
+        assert_eq!(
+            balance_hash(&game_balance),
+            balance_hash(&GameBalance::default())
+        );
+        assert_ne!(
+            balance_hash(&game_balance),
+            balance_hash(&GameBalance {
+                gravity_strength: game_balance.gravity_strength * 2.,
+                ..game_balance.clone()
+            })
+        );
+
+        assert_eq!(
+            planets_hash(&PlanetDefinitions::default()),
+            planets_hash(&PlanetDefinitions::default())
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    game_time::GameTime, pause::PauseState, settings::GameSettings, AppState, HitStop,
+    PlanetActivatedEvent, PlanetClearedEvent, Player,
+};
+
+// Fixed ticks of player states kept for a rewind, about a second with the default 60 Hz.
+pub const REWIND_HISTORY_LENGTH: usize = 60;
+pub const REWIND_INVINCIBILITY_DURATION: f32 = 1.5;
+pub const INVINCIBILITY_BLINK_SPEED: f32 = 20.;
+pub const INVINCIBILITY_MIN_ALPHA: f32 = 0.3;
+
+#[derive(Clone, Copy)]
+pub struct PlayerSnapshot {
+    pub position: Vec3,
+    pub velocity: f32,
+    pub is_grounded: bool,
+}
+
+/// Recent player states of the easy difficulty, to undo a death.
+#[derive(Resource, Default)]
+pub struct StateHistory {
+    pub snapshots: VecDeque<PlayerSnapshot>,
+    /// Rewinds left on the current planet.
+    pub rewinds_left: usize,
+}
+
+/// Hazards don't hurt the player while the timer runs.
+#[derive(Component)]
+pub struct Invincible {
+    pub timer: Timer,
+}
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StateHistory>()
+            .add_systems(OnEnter(AppState::Playing), reset_state_history)
+            .add_systems(OnExit(PauseState::Paused), clear_snapshots)
+            .add_systems(
+                FixedUpdate,
+                record_player_state
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not(resource_exists::<HitStop>())),
+            )
+            .add_systems(
+                Update,
+                (refill_rewinds, tick_invincibility)
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+fn reset_state_history(mut state_history: ResMut<StateHistory>) {
+    state_history.snapshots.clear();
+    state_history.rewinds_left = 0;
+}
+
+// The fixed ticks of the paused time catch up all at once after resuming, and would fill
+// the history with copies of the resume position.
+fn clear_snapshots(mut state_history: ResMut<StateHistory>) {
+    state_history.snapshots.clear();
+}
+
+fn record_player_state(
+    mut state_history: ResMut<StateHistory>,
+    player_query: Query<(&Transform, &Player)>,
+    settings: Res<GameSettings>,
+) {
+    if settings.difficulty.rewinds_per_planet() == 0 {
+        return;
+    }
+
+    let Ok((player_transform, player_struct)) = player_query.get_single() else {
+        return;
+    };
+
+    if state_history.snapshots.len() == REWIND_HISTORY_LENGTH {
+        state_history.snapshots.pop_front();
+    }
+
+    state_history.snapshots.push_back(PlayerSnapshot {
+        position: player_transform.translation,
+        velocity: player_struct.velocity,
+        is_grounded: player_struct.is_grounded,
+    });
+}
+
+// Every planet gets its own rewinds. The states above a cleared planet are useless,
+// since it's gone.
+fn refill_rewinds(
+    mut state_history: ResMut<StateHistory>,
+    mut planet_activated_event_reader: EventReader<PlanetActivatedEvent>,
+    mut planet_cleared_event_reader: EventReader<PlanetClearedEvent>,
+    settings: Res<GameSettings>,
+) {
+    if planet_cleared_event_reader.iter().next().is_some() {
+        state_history.snapshots.clear();
+    }
+
+    if planet_activated_event_reader.iter().next().is_some() {
+        state_history.rewinds_left = settings.difficulty.rewinds_per_planet();
+    }
+}
+
+/// Moves the player to the oldest recorded state and makes them invincible for a moment.
+/// Returns `false`, when there's nothing to rewind to or no rewinds are left.
+pub(crate) fn rewind_player(
+    commands: &mut Commands,
+    player_entity: Entity,
+    player_transform: &mut Transform,
+    player_struct: &mut Player,
+    state_history: &mut StateHistory,
+) -> bool {
+    if state_history.rewinds_left == 0 {
+        return false;
+    }
+
+    let Some(snapshot) = state_history.snapshots.pop_front() else {
+        return false;
+    };
+
+    player_transform.translation = snapshot.position;
+    player_struct.velocity = snapshot.velocity;
+    player_struct.is_grounded = snapshot.is_grounded;
+
+    state_history.snapshots.clear();
+    state_history.rewinds_left -= 1;
+
+    commands.entity(player_entity).insert(Invincible {
+        timer: Timer::from_seconds(REWIND_INVINCIBILITY_DURATION, TimerMode::Once),
+    });
+
+    info!(
+        "Rewound the player, {} rewinds left",
+        state_history.rewinds_left
+    );
+
+    true
+}
+
+// Blinks the player while invincible.
+fn tick_invincibility(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Sprite, &mut Invincible)>,
+    game_time: Res<GameTime>,
+) {
+    for (player_entity, mut player_sprite, mut invincible) in player_query.iter_mut() {
+        if invincible.timer.tick(game_time.delta()).finished() {
+            player_sprite.color.set_a(1.);
+            commands.entity(player_entity).remove::<Invincible>();
+            continue;
+        }
+
+        let blink = (invincible.timer.elapsed_secs() * INVINCIBILITY_BLINK_SPEED).cos() * 0.5 + 0.5;
+        player_sprite
+            .color
+            .set_a(INVINCIBILITY_MIN_ALPHA + (1. - INVINCIBILITY_MIN_ALPHA) * blink);
+    }
+}
@@ -0,0 +1,271 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    assets::GameAssets, balance::GameBalance, orbit_obstacle, planets::PlanetDefinitions, AppState,
+    Obstacle, PlanetVariant, OBSTACLE_SIZE,
+};
+
+/// Opens the sandbox from the main menu and goes back.
+pub const SANDBOX_KEY: KeyCode = KeyCode::F4;
+pub const SANDBOX_EXPORT_KEY: KeyCode = KeyCode::E;
+pub const SANDBOX_CLEAR_KEY: KeyCode = KeyCode::C;
+/// Flips the direction of the next placed obstacles.
+pub const SANDBOX_REVERSE_KEY: KeyCode = KeyCode::R;
+pub const SANDBOX_PLANET: PlanetVariant = PlanetVariant::Earth;
+
+pub const SANDBOX_HINT_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.bottom = Val::Px(12.);
+    style.left = Val::Px(12.);
+    style
+};
+
+/// Despawned when leaving the sandbox.
+#[derive(Component)]
+pub struct SandboxEntity;
+
+#[derive(Component)]
+pub struct SandboxPlanet {
+    radius: f32,
+}
+
+/// Angles of the placed obstacles in degrees, in the order of placement.
+#[derive(Resource, Default)]
+pub struct SandboxPattern {
+    angles: Vec<f32>,
+    clockwise: bool,
+}
+
+/// Lets designers click obstacles onto a planet, watch them orbit and print
+/// the angles as a wave for `planets.ron`. Only in debug builds.
+pub struct SandboxPlugin;
+
+impl Plugin for SandboxPlugin {
+    fn build(&self, app: &mut App) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        app.init_resource::<SandboxPattern>()
+            .add_systems(
+                Update,
+                toggle_sandbox
+                    .run_if(in_state(AppState::Menu).or_else(in_state(AppState::Sandbox))),
+            )
+            .add_systems(OnEnter(AppState::Sandbox), spawn_sandbox)
+            .add_systems(
+                Update,
+                (
+                    place_sandbox_obstacle,
+                    orbit_sandbox_obstacles,
+                    edit_sandbox_pattern,
+                )
+                    .run_if(in_state(AppState::Sandbox)),
+            )
+            .add_systems(OnExit(AppState::Sandbox), despawn_sandbox);
+    }
+}
+
+fn toggle_sandbox(
+    mut next_app_state: ResMut<NextState<AppState>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    app_state: Res<State<AppState>>,
+) {
+    if !keyboard_input.just_pressed(SANDBOX_KEY) {
+        return;
+    }
+
+    next_app_state.set(match app_state.get() {
+        AppState::Sandbox => AppState::Menu,
+        _ => AppState::Sandbox,
+    });
+}
+
+fn spawn_sandbox(
+    mut commands: Commands,
+    mut sandbox_pattern: ResMut<SandboxPattern>,
+    camera_query: Query<&Transform, With<Camera>>,
+    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    planet_definitions: Res<PlanetDefinitions>,
+) {
+    *sandbox_pattern = SandboxPattern::default();
+
+    let planet_def = planet_definitions.get(SANDBOX_PLANET);
+    let planet_position = camera_query
+        .get_single()
+        .map_or(Vec3::ZERO, |camera_transform| {
+            camera_transform.translation.truncate().extend(0.)
+        });
+
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_translation(planet_position),
+            texture: asset_server.load(planet_def.texture.as_str()),
+            sprite: Sprite {
+                custom_size: Some(planet_def.size()),
+                ..default()
+            },
+            ..default()
+        },
+        SandboxPlanet {
+            radius: planet_def.size / 2.,
+        },
+        SandboxEntity,
+    ));
+
+    commands.spawn((
+        TextBundle {
+            style: SANDBOX_HINT_STYLE,
+            text: Text::from_section(
+                format!(
+                    "Click: place obstacle   {:?}: reverse   {:?}: print pattern   {:?}: clear   {:?}: back",
+                    SANDBOX_REVERSE_KEY, SANDBOX_EXPORT_KEY, SANDBOX_CLEAR_KEY, SANDBOX_KEY
+                ),
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            ..default()
+        },
+        SandboxEntity,
+    ));
+}
+
+// Places an obstacle at the angle of the cursor around the planet.
+#[allow(clippy::too_many_arguments)]
+fn place_sandbox_obstacle(
+    mut commands: Commands,
+    mut sandbox_pattern: ResMut<SandboxPattern>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    planet_query: Query<&Transform, With<SandboxPlanet>>,
+    mouse_input: Res<Input<MouseButton>>,
+    asset_server: Res<AssetServer>,
+    game_balance: Res<GameBalance>,
+    planet_definitions: Res<PlanetDefinitions>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Ok(window), Ok((camera, camera_transform)), Ok(planet_transform)) = (
+        window_query.get_single(),
+        camera_query.get_single(),
+        planet_query.get_single(),
+    ) else {
+        return;
+    };
+
+    let Some(cursor_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    let offset = cursor_position - planet_transform.translation.truncate();
+    let angle = offset.y.atan2(offset.x).rem_euclid(std::f32::consts::TAU);
+    let planet_def = planet_definitions.get(SANDBOX_PLANET);
+
+    sandbox_pattern.angles.push(angle.to_degrees().round());
+
+    let angular_velocity = if sandbox_pattern.clockwise {
+        -game_balance.obstacle_movement_speed
+    } else {
+        game_balance.obstacle_movement_speed
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            // In front of the planet.
+            transform: Transform::from_translation(
+                planet_transform.translation.truncate().extend(1.),
+            ),
+            texture: asset_server.load(planet_def.obstacle_texture.as_str()),
+            sprite: Sprite {
+                custom_size: Some(OBSTACLE_SIZE),
+                flip_x: planet_def.obstacle_rotates && angular_velocity > 0.,
+                ..default()
+            },
+            ..default()
+        },
+        Obstacle {
+            angle,
+            angular_velocity,
+            rotates: planet_def.obstacle_rotates,
+            lethal: true,
+        },
+        SandboxEntity,
+    ));
+}
+
+fn orbit_sandbox_obstacles(
+    mut obstacle_query: Query<(&mut Transform, &mut Obstacle), With<SandboxEntity>>,
+    planet_query: Query<(&Transform, &SandboxPlanet), Without<Obstacle>>,
+    time: Res<Time>,
+) {
+    let Ok((planet_transform, sandbox_planet)) = planet_query.get_single() else {
+        return;
+    };
+
+    for (mut obstacle_transform, mut obstacle_struct) in obstacle_query.iter_mut() {
+        let angle_delta = obstacle_struct.angular_velocity * time.delta_seconds();
+
+        orbit_obstacle(
+            &mut obstacle_transform,
+            &mut obstacle_struct,
+            planet_transform.translation,
+            sandbox_planet.radius,
+            angle_delta,
+        );
+    }
+}
+
+fn edit_sandbox_pattern(
+    mut commands: Commands,
+    mut sandbox_pattern: ResMut<SandboxPattern>,
+    obstacle_query: Query<Entity, (With<Obstacle>, With<SandboxEntity>)>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    if keyboard_input.just_pressed(SANDBOX_REVERSE_KEY) {
+        sandbox_pattern.clockwise = !sandbox_pattern.clockwise;
+        info!(
+            "Sandbox obstacles now orbit {}",
+            if sandbox_pattern.clockwise {
+                "clockwise"
+            } else {
+                "counter-clockwise"
+            }
+        );
+    }
+
+    if keyboard_input.just_pressed(SANDBOX_CLEAR_KEY) {
+        sandbox_pattern.angles.clear();
+
+        for obstacle_entity in obstacle_query.iter() {
+            commands.entity(obstacle_entity).despawn_recursive();
+        }
+    }
+
+    // Same format as a wave in `planets.ron`.
+    if keyboard_input.just_pressed(SANDBOX_EXPORT_KEY) {
+        let angles = sandbox_pattern
+            .angles
+            .iter()
+            .map(|angle| format!("{:.1}", angle))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        info!("Sandbox pattern: [{}]", angles);
+    }
+}
+
+fn despawn_sandbox(mut commands: Commands, sandbox_query: Query<Entity, With<SandboxEntity>>) {
+    for sandbox_entity in sandbox_query.iter() {
+        commands.entity(sandbox_entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    leaderboard::{Leaderboard, LEADERBOARD_SAVE_KEY},
+    progress::{Progress, Streak, PROGRESS_SAVE_KEY, STREAK_SAVE_KEY},
+    settings::{GameSettings, SETTINGS_SAVE_KEY},
+};
+
+/// Folder for the save files on native platforms.
+#[cfg(not(target_arch = "wasm32"))]
+pub const SAVE_DIRECTORY: &str = "saves";
+
+/// Prefix of the localStorage keys on WASM.
+#[cfg(target_arch = "wasm32")]
+pub const STORAGE_KEY_PREFIX: &str = "indie_varvars_23.";
+
+/// Loads the value saved under `key`.
+/// Returns `None` if nothing was saved yet or the saved data can't be parsed.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let data = read(key)?;
+
+    match ron::from_str(&data) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            warn!("Failed to parse saved {}: {}", key, error);
+            None
+        }
+    }
+}
+
+/// Saves the value under `key`, overwriting the previous one.
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    if let Some(data) = serialize(key, value) {
+        write(key, &data);
+    }
+}
+
+/// Deletes every save and puts the default resources in place of the loaded ones.
+pub fn reset_all_data(commands: &mut Commands) {
+    for key in [
+        PROGRESS_SAVE_KEY,
+        STREAK_SAVE_KEY,
+        SETTINGS_SAVE_KEY,
+        LEADERBOARD_SAVE_KEY,
+    ] {
+        remove(key);
+    }
+
+    commands.insert_resource(Progress::default());
+    commands.insert_resource(Streak::default());
+    // Added anew rather than overwritten, so the defaults aren't written back to the file
+    // until a setting is changed.
+    commands.remove_resource::<GameSettings>();
+    commands.insert_resource(GameSettings::default());
+    commands.insert_resource(Leaderboard::default());
+
+    info!("All saved data was reset");
+}
+
+fn serialize<T: Serialize>(key: &str, value: &T) -> Option<String> {
+    match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(data) => Some(data),
+        Err(error) => {
+            warn!("Failed to serialize {}: {}", key, error);
+            None
+        }
+    }
+}
+
+/// Stages the value to be saved when the page is closed. No frames run after the
+/// `beforeunload` event on WASM, so it can't be saved on `AppExit` like on native.
+/// A later `save` of the same key discards the staged value.
+#[cfg(target_arch = "wasm32")]
+pub fn save_on_unload<T: Serialize>(key: &str, value: &T) {
+    if let Some(data) = serialize(key, value) {
+        UNLOAD_SAVES.with(|unload_saves| unload_saves.borrow_mut().insert(key.to_string(), data));
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_on_unload<T: Serialize>(_key: &str, _value: &T) {}
+
+/// Writes the values staged by `save_on_unload`, when the page is closed.
+#[cfg(target_arch = "wasm32")]
+pub fn install_unload_hook() {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+
+    let on_unload = Closure::<dyn FnMut()>::new(|| {
+        let unload_saves = UNLOAD_SAVES.with(|unload_saves| unload_saves.take());
+        for (key, data) in unload_saves {
+            write(&key, &data);
+        }
+    });
+
+    if window
+        .add_event_listener_with_callback("beforeunload", on_unload.as_ref().unchecked_ref())
+        .is_err()
+    {
+        warn!("Failed to listen for the page unload, progress may be lost on close");
+    }
+
+    // The hook lives as long as the page.
+    on_unload.forget();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install_unload_hook() {}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static UNLOAD_SAVES: std::cell::RefCell<std::collections::HashMap<String, String>> =
+        Default::default();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn file_path(key: &str) -> std::path::PathBuf {
+    std::path::Path::new(SAVE_DIRECTORY).join(format!("{}.ron", key))
+}
+
+/// Reads the raw saved data under `key`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(file_path(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(key: &str, data: &str) {
+    let result =
+        std::fs::create_dir_all(SAVE_DIRECTORY).and_then(|_| std::fs::write(file_path(key), data));
+
+    if let Err(error) = result {
+        warn!("Failed to save {}: {}", key, error);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn remove(key: &str) {
+    let path = file_path(key);
+
+    if path.exists() {
+        if let Err(error) = std::fs::remove_file(path) {
+            warn!("Failed to delete saved {}: {}", key, error);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Reads the raw saved data under `key`.
+#[cfg(target_arch = "wasm32")]
+pub fn read(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("{}{}", STORAGE_KEY_PREFIX, key))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(key: &str, data: &str) {
+    UNLOAD_SAVES.with(|unload_saves| unload_saves.borrow_mut().remove(key));
+
+    let saved = local_storage()
+        .map(|storage| {
+            storage
+                .set_item(&format!("{}{}", STORAGE_KEY_PREFIX, key), data)
+                .is_ok()
+        })
+        .unwrap_or(false);
+
+    if !saved {
+        warn!("Failed to save {} to localStorage", key);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn remove(key: &str) {
+    UNLOAD_SAVES.with(|unload_saves| unload_saves.borrow_mut().remove(key));
+
+    let removed = local_storage()
+        .map(|storage| {
+            storage
+                .remove_item(&format!("{}{}", STORAGE_KEY_PREFIX, key))
+                .is_ok()
+        })
+        .unwrap_or(false);
+
+    if !removed {
+        warn!("Failed to delete saved {} from localStorage", key);
+    }
+}
@@ -0,0 +1,620 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{progress::Progress, replay::ReplayPlayback, save, PLANET_SHRINK_LIMIT, WINDOW_SIZE};
+
+pub const SETTINGS_SAVE_KEY: &str = "settings";
+/// Bump when `GameSettings` changes in a way `#[serde(default)]` can't handle,
+/// and migrate the older versions in `load_settings`.
+pub const SETTINGS_VERSION: u32 = 1;
+pub const VOLUME_STEP: f32 = 0.25;
+/// Hazard hitbox sizes the player can pick, from the normal one to the most forgiving.
+pub const HITBOX_SCALES: [f32; 3] = [1., 0.85, 0.7];
+/// Multipliers of the obstacle orbit speed, picked in the menu.
+pub const OBSTACLE_SPEED_SCALES: [f32; 5] = [0.5, 0.75, 1., 1.25, 1.5];
+/// Range of `GameSettings::gravity_scale`, changed in steps in the debug panel.
+pub const GRAVITY_SCALE_RANGE: (f32, f32) = (0.2, 3.);
+pub const GRAVITY_SCALE_STEP: f32 = 0.1;
+/// Multipliers of the screen shake, picked in the menu. Zero turns it off.
+pub const SCREEN_SHAKE_INTENSITIES: [f32; 5] = [1., 0.75, 0.5, 0.25, 0.];
+/// Planet targets of the short run, besides the endless one.
+pub const SHORT_RUN_PLANETS: [usize; 3] = [3, 5, 10];
+/// Sizes of the interface in physical pixels per logical pixel, picked in the menu.
+pub const UI_SCALES: [f32; 5] = [1., 1.25, 1.5, 2., 3.];
+
+/// Ready-made key layouts, so players don't have to rebind each key by hand.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum KeyBindingPreset {
+    #[default]
+    Default,
+    /// Jump and fast-fall on the right side of the keyboard.
+    LeftHanded,
+}
+
+impl KeyBindingPreset {
+    pub fn next(self) -> KeyBindingPreset {
+        match self {
+            KeyBindingPreset::Default => KeyBindingPreset::LeftHanded,
+            KeyBindingPreset::LeftHanded => KeyBindingPreset::Default,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyBindingPreset::Default => "Default",
+            KeyBindingPreset::LeftHanded => "Left-handed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "SavedKeyBindings")]
+pub struct KeyBindings {
+    pub preset: KeyBindingPreset,
+    pub jump: KeyCode,
+    pub fast_fall: KeyCode,
+    pub dash: KeyCode,
+}
+
+impl KeyBindings {
+    pub fn from_preset(preset: KeyBindingPreset) -> Self {
+        match preset {
+            KeyBindingPreset::Default => KeyBindings {
+                preset,
+                jump: KeyCode::Space,
+                fast_fall: KeyCode::S,
+                dash: KeyCode::ShiftLeft,
+            },
+            KeyBindingPreset::LeftHanded => KeyBindings {
+                preset,
+                jump: KeyCode::Up,
+                fast_fall: KeyCode::Down,
+                dash: KeyCode::ShiftRight,
+            },
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::from_preset(KeyBindingPreset::default())
+    }
+}
+
+/// `KeyBindings` as saved. Keys missing from older files come from their preset,
+/// not from the default one.
+#[derive(Deserialize)]
+struct SavedKeyBindings {
+    #[serde(default)]
+    preset: KeyBindingPreset,
+    #[serde(default, deserialize_with = "deserialize_some_key")]
+    jump: Option<KeyCode>,
+    #[serde(default, deserialize_with = "deserialize_some_key")]
+    fast_fall: Option<KeyCode>,
+    #[serde(default, deserialize_with = "deserialize_some_key")]
+    dash: Option<KeyCode>,
+}
+
+impl From<SavedKeyBindings> for KeyBindings {
+    fn from(saved_key_bindings: SavedKeyBindings) -> Self {
+        let preset_key_bindings = KeyBindings::from_preset(saved_key_bindings.preset);
+
+        KeyBindings {
+            preset: saved_key_bindings.preset,
+            jump: saved_key_bindings.jump.unwrap_or(preset_key_bindings.jump),
+            fast_fall: saved_key_bindings
+                .fast_fall
+                .unwrap_or(preset_key_bindings.fast_fall),
+            dash: saved_key_bindings.dash.unwrap_or(preset_key_bindings.dash),
+        }
+    }
+}
+
+// The keys are saved without `Some`.
+fn deserialize_some_key<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<KeyCode>, D::Error> {
+    KeyCode::deserialize(deserializer).map(Some)
+}
+
+/// How hard the runs are.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DifficultyPreset {
+    /// Deaths rewind the player a moment back, a few times per planet.
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// Planets shrink to a tiny size before vanishing.
+    Survival,
+}
+
+impl DifficultyPreset {
+    pub fn next(self) -> DifficultyPreset {
+        match self {
+            DifficultyPreset::Easy => DifficultyPreset::Normal,
+            DifficultyPreset::Normal => DifficultyPreset::Hard,
+            DifficultyPreset::Hard => DifficultyPreset::Survival,
+            DifficultyPreset::Survival => DifficultyPreset::Easy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DifficultyPreset::Easy => "Easy",
+            DifficultyPreset::Normal => "Normal",
+            DifficultyPreset::Hard => "Hard",
+            DifficultyPreset::Survival => "Survival",
+        }
+    }
+
+    /// Planet size when it vanishes and the next planet spawns.
+    pub fn shrink_limit(self) -> Vec2 {
+        match self {
+            DifficultyPreset::Easy | DifficultyPreset::Normal => PLANET_SHRINK_LIMIT,
+            DifficultyPreset::Hard => Vec2::new(150., 150.),
+            DifficultyPreset::Survival => Vec2::new(100., 100.),
+        }
+    }
+
+    /// Hits the player takes before the run ends. Falling into the void ends it right away.
+    pub fn lives(self) -> u8 {
+        match self {
+            DifficultyPreset::Easy => 3,
+            DifficultyPreset::Normal => 2,
+            DifficultyPreset::Hard | DifficultyPreset::Survival => 1,
+        }
+    }
+
+    /// How many times a death is undone on a single planet.
+    pub fn rewinds_per_planet(self) -> usize {
+        match self {
+            DifficultyPreset::Easy => 2,
+            DifficultyPreset::Normal | DifficultyPreset::Hard | DifficultyPreset::Survival => 0,
+        }
+    }
+}
+
+/// Ready-made movement feels, setting the gravity, the jump strength and the camera follow
+/// at once, and capping the screen shake. Lower gravity also means higher jumps with more
+/// hang time.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FeelPreset {
+    #[default]
+    Classic,
+    /// High, slow jumps, the camera follows them up.
+    Floaty,
+    /// Short, quick jumps.
+    Snappy,
+}
+
+impl FeelPreset {
+    pub fn next(self) -> FeelPreset {
+        match self {
+            FeelPreset::Classic => FeelPreset::Floaty,
+            FeelPreset::Floaty => FeelPreset::Snappy,
+            FeelPreset::Snappy => FeelPreset::Classic,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FeelPreset::Classic => "Classic",
+            FeelPreset::Floaty => "Floaty",
+            FeelPreset::Snappy => "Snappy",
+        }
+    }
+
+    /// Overwrites the settings of the preset. They can still be changed one by one after.
+    /// The screen shake is only ever lowered, so a player who turned it down keeps it down.
+    pub fn apply(self, settings: &mut GameSettings) {
+        let (gravity_scale, jump_strength_scale, camera_follow, max_screen_shake_intensity) =
+            match self {
+                FeelPreset::Classic => (1., 1., false, 1.),
+                FeelPreset::Floaty => (0.7, 0.9, true, 0.5),
+                FeelPreset::Snappy => (1.5, 1.15, false, 1.),
+            };
+
+        settings.feel = self;
+        settings.gravity_scale = gravity_scale;
+        settings.jump_strength_scale = jump_strength_scale;
+        settings.camera_follow = camera_follow;
+        settings.screen_shake_intensity = settings
+            .screen_shake_intensity
+            .min(max_screen_shake_intensity);
+    }
+}
+
+/// Background music. There is only one track yet, so the menu doesn't offer a choice.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MusicTrack {
+    #[default]
+    FunnyBit,
+}
+
+impl MusicTrack {
+    pub const ALL: [MusicTrack; 1] = [MusicTrack::FunnyBit];
+
+    pub fn audio_path(self) -> &'static str {
+        match self {
+            MusicTrack::FunnyBit => "sounds/2021-10-19_-_Funny_Bit_-_www.FesliyanStudios.com.ogg",
+        }
+    }
+}
+
+/// Framing kept by the letterbox, see `GameSettings::letterbox`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AspectRatio {
+    /// The 840:750 of the launch window.
+    Original,
+    Widescreen,
+}
+
+impl AspectRatio {
+    pub const ALL: [AspectRatio; 2] = [AspectRatio::Original, AspectRatio::Widescreen];
+
+    /// Width divided by height.
+    pub fn ratio(self) -> f32 {
+        match self {
+            AspectRatio::Original => WINDOW_SIZE.x / WINDOW_SIZE.y,
+            AspectRatio::Widescreen => 16. / 9.,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AspectRatio::Original => "Original",
+            AspectRatio::Widescreen => "16:9",
+        }
+    }
+}
+
+/// Player sprite, unlocked by playing the game.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum PlayerSkin {
+    #[default]
+    Piggy,
+    Wolf,
+    Ball,
+}
+
+impl PlayerSkin {
+    pub const ALL: [PlayerSkin; 3] = [PlayerSkin::Piggy, PlayerSkin::Wolf, PlayerSkin::Ball];
+
+    pub fn texture_path(self) -> &'static str {
+        match self {
+            PlayerSkin::Piggy => "art/Piggy.png",
+            PlayerSkin::Wolf => "art/Wolf.png",
+            PlayerSkin::Ball => "art/ball.png",
+        }
+    }
+
+    pub fn is_unlocked(self, progress: &Progress) -> bool {
+        match self {
+            PlayerSkin::Piggy => true,
+            PlayerSkin::Wolf => progress.best_score >= 7,
+            PlayerSkin::Ball => progress.total_planets_cleared >= 25,
+        }
+    }
+
+    pub fn unlock_hint(self) -> &'static str {
+        match self {
+            PlayerSkin::Piggy => "",
+            PlayerSkin::Wolf => "Best 7",
+            PlayerSkin::Ball => "25 total",
+        }
+    }
+}
+
+/// Player preferences, saved whenever they change.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    pub key_bindings: KeyBindings,
+    /// Volume of the sound effects, from 0 to 1.
+    pub sfx_volume: f32,
+    /// Volume of the music, from 0 to 1.
+    pub music_volume: f32,
+    pub player_skin: PlayerSkin,
+    /// Render the collision circles all the time, not only while G is held.
+    pub always_show_colliders: bool,
+    /// The first planet of a run has at most one slow obstacle.
+    pub first_planet_grace: bool,
+    /// The camera follows the player up during high jumps.
+    pub camera_follow: bool,
+    /// Pause the run when the game window loses focus.
+    pub pause_on_focus_loss: bool,
+    pub difficulty: DifficultyPreset,
+    /// The fast-fall key pushes the player up instead.
+    pub invert_fast_fall: bool,
+    /// Multiplier of the obstacle and projectile collider radius, one of `HITBOX_SCALES`.
+    /// The player collider keeps its size, so landing on planets looks the same.
+    pub hitbox_scale: f32,
+    /// Streaks and a flash while moving to the next planet, instead of the calm pan.
+    pub warp_effect: bool,
+    /// Keep a small hint with the current keys on screen during runs.
+    pub show_controls_hint: bool,
+    /// Ends the run with a victory after this many planets, one of `SHORT_RUN_PLANETS`.
+    pub short_run_planets: Option<usize>,
+    /// Infinite mode follows the boss planet, otherwise the run ends with a victory.
+    pub endless_after_story: bool,
+    /// The player jumps again right after landing. Holding the jump key keeps them on the planet.
+    pub auto_jump: bool,
+    /// Size of the fonts and the interface, one of `UI_SCALES`.
+    /// `None` follows the scale factor of the window.
+    pub ui_scale: Option<f32>,
+    /// The camera jumps to the next planet, instead of panning over.
+    pub instant_camera_cut: bool,
+    /// The HUD is hidden during runs, toggled with `HUD_TOGGLE_KEY`.
+    pub hide_hud: bool,
+    /// New obstacles appear one per frame, instead of all at once.
+    pub stagger_obstacle_spawn: bool,
+    pub music: MusicTrack,
+    /// The camera turns with the player around the planet, so the player stays upright.
+    pub rotate_camera: bool,
+    /// Multiplier of the obstacle orbit speed, one of `OBSTACLE_SPEED_SCALES`.
+    /// Stacks with the slower obstacles of the first planet.
+    pub obstacle_speed_scale: f32,
+    /// Bars around the view keep this aspect ratio on any window. `None` fills the window.
+    pub letterbox: Option<AspectRatio>,
+    /// Exact player values in a corner during runs, toggled with `SPEEDRUN_OVERLAY_KEY`.
+    pub speedrun_overlay: bool,
+    /// Multiplier of `GameBalance::gravity_strength`, for experiments in the debug panel.
+    pub gravity_scale: f32,
+    /// Multiplier of `GameBalance::player_jump_strength`, set by the `FeelPreset`.
+    pub jump_strength_scale: f32,
+    /// Hazards don't hurt and falling off puts the player back, for looking around.
+    /// Runs don't go on the leaderboard.
+    pub freeplay: bool,
+    /// Multiplier of every screen shake, one of `SCREEN_SHAKE_INTENSITIES`.
+    pub screen_shake_intensity: f32,
+    /// The planet shrinks a bit slower while the player is high up in a jump.
+    pub airborne_shrink_slowdown: bool,
+    /// Obstacle layouts are mirrored left to right, and everything orbits the other way.
+    pub mirror_level: bool,
+    /// The last picked `FeelPreset`.
+    pub feel: FeelPreset,
+    /// Story patterns are turned by a random angle each run, so they can't be just memorized.
+    pub shifted_story: bool,
+    /// Faint arcs ahead of the obstacles on the current planet, showing where they go next.
+    pub show_obstacle_trajectories: bool,
+    /// Tick marks with degrees around the current planet, see `AngleGuidePlugin`.
+    pub show_angle_guide: bool,
+    /// Obstacles stand still on the surface and turn with the planet, instead of orbiting
+    /// on their own. The challenge is the planet turning under the player, not sweeping obstacles.
+    pub obstacles_follow_planet: bool,
+    /// Menus and end screens redraw at most every `MENU_FRAME_TIME`, to save power.
+    pub menu_frame_limit: bool,
+    /// Releasing the jump key early makes a lower jump, see `PLAYER_JUMP_CUT`.
+    /// Off, every jump has the full height.
+    pub variable_jump: bool,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings {
+            key_bindings: KeyBindings::default(),
+            sfx_volume: 1.,
+            music_volume: 1.,
+            player_skin: PlayerSkin::default(),
+            always_show_colliders: false,
+            first_planet_grace: true,
+            camera_follow: false,
+            pause_on_focus_loss: true,
+            difficulty: DifficultyPreset::default(),
+            invert_fast_fall: false,
+            hitbox_scale: 1.,
+            warp_effect: false,
+            show_controls_hint: false,
+            short_run_planets: None,
+            endless_after_story: true,
+            auto_jump: false,
+            ui_scale: None,
+            instant_camera_cut: false,
+            hide_hud: false,
+            stagger_obstacle_spawn: false,
+            music: MusicTrack::default(),
+            rotate_camera: false,
+            obstacle_speed_scale: 1.,
+            letterbox: None,
+            speedrun_overlay: false,
+            gravity_scale: 1.,
+            jump_strength_scale: 1.,
+            freeplay: false,
+            screen_shake_intensity: 1.,
+            airborne_shrink_slowdown: false,
+            mirror_level: false,
+            feel: FeelPreset::default(),
+            shifted_story: false,
+            show_obstacle_trajectories: false,
+            show_angle_guide: false,
+            obstacles_follow_planet: false,
+            menu_frame_limit: true,
+            variable_jump: true,
+        }
+    }
+}
+
+/// Settings file format. Fields added to `GameSettings` later get their defaults,
+/// when an older file is loaded.
+#[derive(Serialize, Deserialize)]
+pub struct SettingsV1 {
+    pub version: u32,
+    pub settings: GameSettings,
+}
+
+/// Loads the saved settings, migrating them from older formats.
+/// Missing or corrupt files give the default settings.
+pub fn load_settings() -> GameSettings {
+    let Some(data) = save::read(SETTINGS_SAVE_KEY) else {
+        return GameSettings::default();
+    };
+
+    if let Ok(settings_file) = ron::from_str::<SettingsV1>(&data) {
+        if settings_file.version > SETTINGS_VERSION {
+            warn!(
+                "Settings were saved by a newer version {}, unknown fields are ignored",
+                settings_file.version
+            );
+        }
+
+        return settings_file.settings;
+    }
+
+    // Settings saved before the versioning are a bare `GameSettings`.
+    match ron::from_str::<GameSettings>(&data) {
+        Ok(settings) => {
+            info!(
+                "Migrated unversioned settings to version {}",
+                SETTINGS_VERSION
+            );
+            settings
+        }
+        Err(error) => {
+            warn!("Failed to load settings, using the defaults: {}", error);
+            GameSettings::default()
+        }
+    }
+}
+
+pub fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+/// Returns the next volume step, going back to mute after the full volume.
+pub fn next_volume_step(volume: f32) -> f32 {
+    if volume >= 1. {
+        0.
+    } else {
+        (volume + VOLUME_STEP).min(1.)
+    }
+}
+
+/// Returns the next of `HITBOX_SCALES`, going back to the normal size after the smallest.
+pub fn next_hitbox_scale(hitbox_scale: f32) -> f32 {
+    HITBOX_SCALES
+        .iter()
+        .copied()
+        .find(|&scale| scale < hitbox_scale)
+        .unwrap_or(HITBOX_SCALES[0])
+}
+
+/// Returns the next of `OBSTACLE_SPEED_SCALES`, going back to the slowest after the fastest.
+pub fn next_obstacle_speed_scale(obstacle_speed_scale: f32) -> f32 {
+    OBSTACLE_SPEED_SCALES
+        .iter()
+        .copied()
+        .find(|&scale| scale > obstacle_speed_scale)
+        .unwrap_or(OBSTACLE_SPEED_SCALES[0])
+}
+
+/// Returns the next of `SCREEN_SHAKE_INTENSITIES`, going back to the full shake after none.
+pub fn next_screen_shake_intensity(screen_shake_intensity: f32) -> f32 {
+    SCREEN_SHAKE_INTENSITIES
+        .iter()
+        .copied()
+        .find(|&intensity| intensity < screen_shake_intensity)
+        .unwrap_or(SCREEN_SHAKE_INTENSITIES[0])
+}
+
+/// Returns the next of `SHORT_RUN_PLANETS`, going back to the endless run after the longest.
+pub fn next_short_run_planets(short_run_planets: Option<usize>) -> Option<usize> {
+    match short_run_planets {
+        None => Some(SHORT_RUN_PLANETS[0]),
+        Some(planets) => SHORT_RUN_PLANETS
+            .iter()
+            .copied()
+            .find(|&target| target > planets),
+    }
+}
+
+/// Returns the next of `UI_SCALES`, going back to following the window after the largest.
+pub fn next_ui_scale(ui_scale: Option<f32>) -> Option<f32> {
+    match ui_scale {
+        None => Some(UI_SCALES[0]),
+        Some(ui_scale) => UI_SCALES.iter().copied().find(|&scale| scale > ui_scale),
+    }
+}
+
+/// Returns the next of `AspectRatio::ALL`, going back to filling the window after the last.
+pub fn next_letterbox(letterbox: Option<AspectRatio>) -> Option<AspectRatio> {
+    match letterbox {
+        None => Some(AspectRatio::ALL[0]),
+        Some(aspect_ratio) => AspectRatio::ALL
+            .iter()
+            .position(|&other_ratio| other_ratio == aspect_ratio)
+            .and_then(|index| AspectRatio::ALL.get(index + 1))
+            .copied(),
+    }
+}
+
+/// The chosen UI scale, or the step of `UI_SCALES` closest to the window scale factor.
+pub fn resolve_ui_scale(ui_scale: Option<f32>, window_scale_factor: f64) -> f32 {
+    ui_scale.unwrap_or_else(|| {
+        let window_scale_factor = window_scale_factor as f32;
+
+        UI_SCALES
+            .iter()
+            .copied()
+            .min_by(|a, b| {
+                (a - window_scale_factor)
+                    .abs()
+                    .total_cmp(&(b - window_scale_factor).abs())
+            })
+            .unwrap_or(1.)
+    })
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_settings())
+            .add_systems(Update, save_settings);
+    }
+}
+
+/// Writes the settings in the current `SettingsV1` format.
+pub fn write_settings(settings: &GameSettings) {
+    save::save(
+        SETTINGS_SAVE_KEY,
+        &SettingsV1 {
+            version: SETTINGS_VERSION,
+            settings: settings.clone(),
+        },
+    );
+}
+
+// The settings of a replay are only borrowed for its playback.
+fn save_settings(settings: Res<GameSettings>, playback: Option<Res<ReplayPlayback>>) {
+    if settings.is_changed() && !settings.is_added() && playback.is_none() {
+        write_settings(&settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_come_from_the_saved_preset() {
+        let key_bindings: KeyBindings =
+            ron::from_str("(preset: LeftHanded, jump: Up, fast_fall: Down)").unwrap();
+
+        assert_eq!(key_bindings.preset, KeyBindingPreset::LeftHanded);
+        assert_eq!(key_bindings.dash, KeyCode::ShiftRight);
+    }
+
+    #[test]
+    fn saved_keys_are_kept() {
+        let key_bindings: KeyBindings = ron::from_str("(jump: W)").unwrap();
+
+        assert_eq!(key_bindings.preset, KeyBindingPreset::Default);
+        assert_eq!(key_bindings.jump, KeyCode::W);
+        assert_eq!(key_bindings.dash, KeyCode::ShiftLeft);
+    }
+}
@@ -0,0 +1,389 @@
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+
+use crate::{
+    assets::GameAssets,
+    menu::{
+        HOVERED_SETTING_BUTTON_COLOR, NORMAL_SETTING_BUTTON_COLOR, PRESSED_SETTING_BUTTON_COLOR,
+        SETTING_BUTTON_STYLE,
+    },
+    progress::Progress,
+    AppState, GameManager, GameRng,
+};
+
+/// Folder for the share cards on native platforms, the web build downloads them instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub const SHARE_CARD_DIRECTORY: &str = "share_cards";
+/// How long the card stays on screen after the capture.
+pub const SHARE_CARD_DURATION: f32 = 1.5;
+pub const SHARE_CARD_BACKGROUND_COLOR: Color = Color::rgba(0.05, 0.05, 0.15, 0.85);
+pub const SHARE_CARD_TITLE_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+
+pub const SHARE_BUTTON_CONTAINER_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.justify_content = JustifyContent::Center;
+    style.bottom = Val::Percent(2.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const SHARE_CARD_CONTAINER_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.justify_content = JustifyContent::Center;
+    style.top = Val::Percent(20.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const SHARE_CARD_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.flex_direction = FlexDirection::Column;
+    style.align_items = AlignItems::Center;
+    style.padding = UiRect::all(Val::Px(24.));
+    style
+};
+
+/// Despawned when leaving the end screen.
+#[derive(Component)]
+pub struct ShareButton;
+
+/// Copies the seed of the run, so it can be pasted into the seed entry of the menu.
+#[derive(Component)]
+pub struct CopySeedButton;
+
+/// Score overlay, that is on screen while the share card is captured.
+#[derive(Component)]
+pub struct ShareCard {
+    timer: Timer,
+}
+
+/// Saves a screenshot of the end screen with the score, best, planets cleared and seed on it,
+/// so players can share their runs. The seed can also be copied on its own.
+pub struct SharePlugin;
+
+impl Plugin for SharePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::GameOver), spawn_share_button)
+            .add_systems(OnEnter(AppState::Victory), spawn_share_button)
+            .add_systems(
+                Update,
+                (
+                    interact_with_share_button,
+                    interact_with_copy_seed_button,
+                    hide_share_card,
+                )
+                    .run_if(in_state(AppState::GameOver).or_else(in_state(AppState::Victory))),
+            )
+            .add_systems(OnExit(AppState::GameOver), despawn_share_ui)
+            .add_systems(OnExit(AppState::Victory), despawn_share_ui);
+    }
+}
+
+fn spawn_share_button(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: SHARE_BUTTON_CONTAINER_STYLE,
+                ..default()
+            },
+            ShareButton,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: SETTING_BUTTON_STYLE,
+                        background_color: NORMAL_SETTING_BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    ShareButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Save share card",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 28.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: SETTING_BUTTON_STYLE,
+                        background_color: NORMAL_SETTING_BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    CopySeedButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Copy seed",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 28.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+        });
+}
+
+fn interact_with_copy_seed_button(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<CopySeedButton>),
+    >,
+    game_rng: Res<GameRng>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+                copy_to_clipboard(&game_rng.seed.to_string());
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+// Shows the card and captures the frame it's first drawn in.
+#[allow(clippy::too_many_arguments)]
+fn interact_with_share_button(
+    mut commands: Commands,
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ShareButton>),
+    >,
+    mut share_button_query: Query<&mut Visibility, (With<ShareButton>, Without<Parent>)>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    share_card_query: Query<(), With<ShareCard>>,
+    game_assets: Res<GameAssets>,
+    game_manager: Res<GameManager>,
+    game_rng: Res<GameRng>,
+    progress: Res<Progress>,
+) {
+    let Ok((interaction, mut background_color)) = button_query.get_single_mut() else {
+        return;
+    };
+
+    match *interaction {
+        Interaction::Pressed => {
+            *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+        }
+        Interaction::Hovered => {
+            *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            return;
+        }
+        Interaction::None => {
+            *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            return;
+        }
+    }
+
+    if !share_card_query.is_empty() {
+        return;
+    }
+
+    let Ok(window_entity) = window_query.get_single() else {
+        return;
+    };
+
+    let file_name = format!(
+        "share-card-{}-{}.png",
+        game_rng.seed,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+
+    if let Err(error) = screenshot_manager.take_screenshot(window_entity, move |screenshot| {
+        match screenshot.try_into_dynamic() {
+            // The alpha channel of the frame isn't meant to be seen.
+            Ok(image) => export_share_card(&file_name, image.to_rgb8()),
+            Err(error) => warn!("Failed to convert the share card: {}", error),
+        }
+    }) {
+        warn!("Failed to capture the share card: {}", error);
+        return;
+    }
+
+    // The button shouldn't end up on the card.
+    if let Ok(mut share_button_visibility) = share_button_query.get_single_mut() {
+        *share_button_visibility = Visibility::Hidden;
+    }
+
+    // The last run is already recorded in the progress, unless it's the first.
+    let best_score = progress.best_score.max(game_manager.score);
+    let lines = [
+        format!("Score: {}", game_manager.score),
+        format!("Best: {}", best_score),
+        format!("Planets cleared: {}", game_manager.planets_cleared),
+        format!("Seed: {}", game_rng.seed),
+    ];
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: SHARE_CARD_CONTAINER_STYLE,
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            ShareCard {
+                timer: Timer::from_seconds(SHARE_CARD_DURATION, TimerMode::Once),
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: SHARE_CARD_STYLE,
+                    background_color: SHARE_CARD_BACKGROUND_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Indie Varvar's 2023",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 56.0,
+                            color: SHARE_CARD_TITLE_COLOR,
+                        },
+                    ));
+
+                    for line in lines {
+                        parent.spawn(TextBundle::from_section(
+                            line,
+                            TextStyle {
+                                font: game_assets.font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    }
+                });
+        });
+}
+
+fn hide_share_card(
+    mut commands: Commands,
+    mut share_card_query: Query<(Entity, &mut ShareCard)>,
+    mut share_button_query: Query<&mut Visibility, (With<ShareButton>, Without<Parent>)>,
+    time: Res<Time>,
+) {
+    for (share_card_entity, mut share_card) in share_card_query.iter_mut() {
+        if !share_card.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        commands.entity(share_card_entity).despawn_recursive();
+
+        if let Ok(mut share_button_visibility) = share_button_query.get_single_mut() {
+            *share_button_visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn despawn_share_ui(
+    mut commands: Commands,
+    share_ui_query: Query<Entity, (Or<(With<ShareButton>, With<ShareCard>)>, Without<Parent>)>,
+) {
+    for share_ui_entity in share_ui_query.iter() {
+        commands.entity(share_ui_entity).despawn_recursive();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn copy_to_clipboard(text: &str) {
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text)) {
+        Ok(()) => info!("Copied {} to the clipboard", text),
+        Err(error) => warn!("Failed to copy to the clipboard: {}", error),
+    }
+}
+
+// The clipboard API of web-sys is unstable, so it's called through the JS object instead.
+#[cfg(target_arch = "wasm32")]
+fn copy_to_clipboard(text: &str) {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let write_text = || -> Option<()> {
+        let navigator = web_sys::window()?.navigator();
+        let clipboard = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard")).ok()?;
+        let write_text = js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))
+            .ok()?
+            .dyn_into::<js_sys::Function>()
+            .ok()?;
+
+        write_text
+            .call1(&clipboard, &JsValue::from_str(text))
+            .ok()?;
+        Some(())
+    };
+
+    if write_text().is_none() {
+        warn!("Failed to copy to the clipboard");
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export_share_card(file_name: &str, card: image::RgbImage) {
+    let path = std::path::Path::new(SHARE_CARD_DIRECTORY).join(file_name);
+    let result = std::fs::create_dir_all(SHARE_CARD_DIRECTORY)
+        .map_err(|error| error.to_string())
+        .and_then(|_| card.save(&path).map_err(|error| error.to_string()));
+
+    match result {
+        Ok(()) => info!("Saved the share card to {}", path.display()),
+        Err(error) => warn!("Failed to save the share card: {}", error),
+    }
+}
+
+// The web build has no file system, the browser downloads the card instead.
+#[cfg(target_arch = "wasm32")]
+fn export_share_card(file_name: &str, card: image::RgbImage) {
+    let mut png = Vec::new();
+    if let Err(error) = card.write_to(
+        &mut std::io::Cursor::new(&mut png),
+        image::ImageOutputFormat::Png,
+    ) {
+        warn!("Failed to encode the share card: {}", error);
+        return;
+    }
+
+    if download(file_name, &png).is_none() {
+        warn!("Failed to download the share card");
+    }
+}
+
+// Clicks a temporary link to the PNG, which makes the browser save it.
+#[cfg(target_arch = "wasm32")]
+fn download(file_name: &str, png: &[u8]) -> Option<()> {
+    use wasm_bindgen::JsCast;
+
+    let document = web_sys::window()?.document()?;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(png));
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(
+        &parts,
+        web_sys::BlobPropertyBag::new().type_("image/png"),
+    )
+    .ok()?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).ok()?;
+
+    let link = document
+        .create_element("a")
+        .ok()?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .ok()?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+
+    web_sys::Url::revoke_object_url(&url).ok()
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameAssets, pause::PauseState, settings::GameSettings, AppState, CurrentPlanet, Planet,
+    Player, PlayerLandedEvent,
+};
+
+pub const SPEEDRUN_OVERLAY_KEY: KeyCode = KeyCode::F5;
+
+pub const SPEEDRUN_OVERLAY_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.top = Val::Px(12.);
+    style.right = Val::Px(12.);
+    style
+};
+
+pub const SPEEDRUN_OVERLAY_BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.6);
+
+#[derive(Component)]
+pub struct SpeedrunOverlay;
+
+/// Gameplay frames since the player last landed on a planet.
+#[derive(Resource, Default)]
+pub struct FramesSinceLanding(pub u64);
+
+/// Exact player values for speedrunners, shown during runs with the speedrun overlay
+/// setting, also in release builds. Toggled with `SPEEDRUN_OVERLAY_KEY`.
+pub struct SpeedrunPlugin;
+
+impl Plugin for SpeedrunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FramesSinceLanding>()
+            .add_systems(Startup, spawn_speedrun_overlay)
+            .add_systems(OnEnter(AppState::Playing), reset_frames_since_landing)
+            .add_systems(
+                Update,
+                count_frames_since_landing
+                    .after(crate::check_player_planet_collisions)
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_speedrun_overlay.run_if(in_state(AppState::Playing)),
+                    update_speedrun_overlay,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn spawn_speedrun_overlay(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.spawn((
+        TextBundle {
+            style: SPEEDRUN_OVERLAY_STYLE,
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            ),
+            background_color: SPEEDRUN_OVERLAY_BACKGROUND_COLOR.into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(15),
+            ..default()
+        },
+        SpeedrunOverlay,
+    ));
+}
+
+fn reset_frames_since_landing(mut frames_since_landing: ResMut<FramesSinceLanding>) {
+    frames_since_landing.0 = 0;
+}
+
+fn count_frames_since_landing(
+    mut frames_since_landing: ResMut<FramesSinceLanding>,
+    mut player_landed_event_reader: EventReader<PlayerLandedEvent>,
+) {
+    if player_landed_event_reader.iter().last().is_some() {
+        frames_since_landing.0 = 0;
+    } else {
+        frames_since_landing.0 += 1;
+    }
+}
+
+fn toggle_speedrun_overlay(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    if keyboard_input.just_pressed(SPEEDRUN_OVERLAY_KEY) {
+        settings.speedrun_overlay = !settings.speedrun_overlay;
+    }
+}
+
+fn update_speedrun_overlay(
+    mut overlay_query: Query<(&mut Text, &mut Visibility), With<SpeedrunOverlay>>,
+    player_query: Query<(&Transform, &Player)>,
+    planet_query: Query<&Transform, With<Planet>>,
+    current_planet: Res<CurrentPlanet>,
+    frames_since_landing: Res<FramesSinceLanding>,
+    settings: Res<GameSettings>,
+    app_state: Res<State<AppState>>,
+) {
+    let Ok((mut text, mut visibility)) = overlay_query.get_single_mut() else {
+        return;
+    };
+
+    let is_shown = settings.speedrun_overlay && *app_state.get() == AppState::Playing;
+    *visibility = if is_shown {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    if !is_shown {
+        return;
+    }
+
+    let Ok((player_transform, player_struct)) = player_query.get_single() else {
+        return;
+    };
+
+    // Counter-clockwise from the right, like the obstacle angles.
+    let player_angle = current_planet
+        .entity
+        .and_then(|planet_entity| planet_query.get(planet_entity).ok())
+        .map_or("-".to_string(), |planet_transform| {
+            let offset = (player_transform.translation - planet_transform.translation).truncate();
+            format!(
+                "{:7.2}°",
+                offset.y.atan2(offset.x).to_degrees().rem_euclid(360.)
+            )
+        });
+
+    text.sections[0].value = [
+        format!("Y:        {:9.2}", player_transform.translation.y),
+        format!("Velocity: {:9.2}", player_struct.velocity),
+        format!("Angle:    {:>9}", player_angle),
+        format!("Radius:   {:9.2}", current_planet.radius),
+        format!("Frames since landing: {}", frames_since_landing.0),
+    ]
+    .join("\n");
+}
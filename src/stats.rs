@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_time::GameTime, pause::PauseState, AppState, Combo, PlanetActivatedEvent,
+    PlanetClearedEvent, PlanetVariant, ScoreEvent, ScoreReason,
+};
+
+/// Statistics of the current run, shown on the game over screen. Reset in `start_game`.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RunStats {
+    pub max_combo: usize,
+    /// Obstacles of the planets the player outlasted.
+    pub obstacles_dodged: usize,
+    pub near_misses: usize,
+    pub survival_time: f32,
+    pub planets_cleared: usize,
+    /// The last planet the player landed on.
+    pub furthest_planet: Option<PlanetVariant>,
+}
+
+impl RunStats {
+    pub fn to_text(&self) -> String {
+        let furthest_planet = self
+            .furthest_planet
+            .map_or("None", |planet_variant| planet_variant.name());
+
+        [
+            format!("Survived: {:.1}s", self.survival_time),
+            format!(
+                "Furthest planet: {} ({} cleared)",
+                furthest_planet, self.planets_cleared
+            ),
+            format!("Max combo: {}", self.max_combo),
+            format!("Obstacles dodged: {}", self.obstacles_dodged),
+            format!("Near misses: {}", self.near_misses),
+        ]
+        .join("\n")
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>().add_systems(
+            Update,
+            // Same frame as the last planet of a short run, before the victory screen.
+            track_run_stats
+                .after(crate::shrink_current_planet)
+                .run_if(in_state(AppState::Playing))
+                .run_if(in_state(PauseState::Running)),
+        );
+    }
+}
+
+fn track_run_stats(
+    mut run_stats: ResMut<RunStats>,
+    mut planet_cleared_event_reader: EventReader<PlanetClearedEvent>,
+    mut planet_activated_event_reader: EventReader<PlanetActivatedEvent>,
+    mut score_event_reader: EventReader<ScoreEvent>,
+    combo: Res<Combo>,
+    game_time: Res<GameTime>,
+) {
+    run_stats.survival_time += game_time.delta_seconds();
+    run_stats.max_combo = run_stats.max_combo.max(combo.count);
+
+    for planet_cleared_event in planet_cleared_event_reader.iter() {
+        run_stats.planets_cleared += 1;
+        run_stats.obstacles_dodged += planet_cleared_event.obstacles;
+    }
+
+    for planet_activated_event in planet_activated_event_reader.iter() {
+        run_stats.furthest_planet = Some(planet_activated_event.variant);
+    }
+
+    for score_event in score_event_reader.iter() {
+        if matches!(score_event.reason, ScoreReason::NearMiss) {
+            run_stats.near_misses += 1;
+        }
+    }
+}
@@ -1,7 +1,24 @@
-use crate::GameManager;
+use crate::{
+    assets::GameAssets,
+    menu::{
+        HOVERED_SETTING_BUTTON_COLOR, NORMAL_SETTING_BUTTON_COLOR, PRESSED_SETTING_BUTTON_COLOR,
+        SETTING_BUTTON_STYLE,
+    },
+    modifiers::RunModifiers,
+    settings::{resolve_ui_scale, GameSettings},
+    stats::RunStats,
+    Combo, ComboBrokenEvent, DeathCause, GameManager, GameRng, PlanetActivatedEvent, Player,
+    ScoreEvent, ScoreReason,
+};
 
 use super::AppState;
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowScaleFactorChanged},
+};
+
+/// Hides the HUD during runs, for screenshots.
+pub const HUD_TOGGLE_KEY: KeyCode = KeyCode::H;
 
 pub const MAIN_HUD_STYLE: Style = {
     let mut style = Style::DEFAULT;
@@ -44,37 +61,356 @@ pub const TEXT_STYLE: Style = {
     style
 };
 
+pub const GO_TEXT_CONTAINER_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.justify_content = JustifyContent::Center;
+    style.top = Val::Percent(20.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const PLANET_NAME_CONTAINER_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.justify_content = JustifyContent::Center;
+    style.top = Val::Percent(8.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const FREEPLAY_TEXT_CONTAINER_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.justify_content = JustifyContent::Center;
+    style.bottom = Val::Px(24.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const DEATH_CAUSE_CONTAINER_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.flex_direction = FlexDirection::Column;
+    style.align_items = AlignItems::Center;
+    style.bottom = Val::Percent(12.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const CLOSE_TEXT_CONTAINER_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.justify_content = JustifyContent::Center;
+    style.top = Val::Percent(35.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const COMBO_TEXT_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.top = Val::Px(COMBO_TEXT_OFFSET.y);
+    style.right = Val::Px(COMBO_TEXT_OFFSET.x);
+    style
+};
+
+pub const LIVES_TEXT_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.top = Val::Px(24.);
+    style.left = Val::Px(24.);
+    style
+};
+
+// Below the lives.
+pub const MODIFIERS_TEXT_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.top = Val::Px(72.);
+    style.left = Val::Px(24.);
+    style
+};
+
+pub const DASH_TEXT_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.bottom = Val::Px(24.);
+    style.left = Val::Px(24.);
+    style
+};
+
+pub const VICTORY_SCREEN_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.flex_direction = FlexDirection::Column;
+    style.align_items = AlignItems::Center;
+    style.top = Val::Percent(8.);
+    style.width = Val::Percent(100.);
+    style
+};
+
+pub const VICTORY_TITLE_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+
+pub const CONTROLS_HINT_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.bottom = Val::Px(64.);
+    style.left = Val::Px(24.);
+    style
+};
+
+pub const CONTROLS_HINT_COLOR: Color = Color::rgba(1., 1., 1., 0.7);
+
+pub const VOLUME_OVERLAY_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.flex_direction = FlexDirection::Column;
+    style.top = Val::Px(24.);
+    style.left = Val::Px(24.);
+    style.padding = UiRect::all(Val::Px(12.));
+    style
+};
+
+pub const VOLUME_ROW_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.flex_direction = FlexDirection::Row;
+    style.align_items = AlignItems::Center;
+    style.margin = UiRect::vertical(Val::Px(4.));
+    style
+};
+
+pub const VOLUME_LABEL_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.width = Val::Px(90.);
+    style
+};
+
+pub const VOLUME_BAR_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.width = Val::Px(200.);
+    style.height = Val::Px(16.);
+    style
+};
+
+pub const VOLUME_BAR_FILL_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.height = Val::Percent(100.);
+    style
+};
+
+pub const VOLUME_OVERLAY_BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.6);
+pub const VOLUME_BAR_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+pub const VOLUME_BAR_FILL_COLOR: Color = Color::WHITE;
+// How long the volume overlay stays on screen after the last volume change.
+pub const VOLUME_OVERLAY_DURATION: f32 = 2.;
+
+pub const DASH_READY_COLOR: Color = Color::WHITE;
+pub const DASH_COOLDOWN_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+
+pub const COMBO_TEXT_OFFSET: Vec2 = Vec2::new(24., 96.);
+
+pub const LIFE_ICON: &str = "♥";
+pub const LIFE_COLOR: Color = Color::rgb(0.9, 0.2, 0.25);
+pub const COMBO_BREAK_DURATION: f32 = 0.6;
+pub const COMBO_BREAK_SHAKE_STRENGTH: f32 = 8.;
+pub const COMBO_BREAK_COLOR: Color = Color::rgb(1., 0.2, 0.2);
+
+// How long the "GO!" text stays on screen when the planet starts shrinking.
+pub const GO_TEXT_DURATION: f32 = 0.8;
+pub const PLANET_NAME_DURATION: f32 = 1.5;
+pub const CLOSE_TEXT_DURATION: f32 = 0.6;
+pub const CLOSE_TEXT_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+
 pub const NORMAL_BUTTON_COLOR: Color = Color::rgb(1., 1., 1.);
 pub const HOVERED_BUTTON_COLOR: Color = Color::rgb(0.75, 0.75, 0.75);
 pub const PRESSED_BUTTON_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
 
+/// Root node of the HUD.
+#[derive(Component)]
+pub struct MainHud;
+
 #[derive(Component)]
 pub struct ReplayButton;
 
 #[derive(Component)]
 pub struct ScoreText;
 
+#[derive(Component)]
+pub struct ComboText;
+
+#[derive(Component)]
+pub struct DashCooldownText;
+
+/// A heart for every life left.
+#[derive(Component)]
+pub struct LivesText;
+
+/// Persistent hint with the current keys, shown during runs when enabled in the settings.
+#[derive(Component)]
+pub struct ControlsHint;
+
+/// Red flash and shake of the combo text, when the combo is lost.
+#[derive(Component)]
+pub struct ComboBreakAnimation {
+    timer: Timer,
+}
+
+/// Popup text, that fades out after being shown.
+#[derive(Component)]
+pub struct FadingText {
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct GoText;
+
+#[derive(Component)]
+pub struct CloseText;
+
+#[derive(Component)]
+pub struct PlanetNameText;
+
+/// Modifiers of the current run, one per line.
+#[derive(Component)]
+pub struct ModifiersText;
+
+/// Reminds, that hazards are harmless during freeplay runs.
+#[derive(Component)]
+pub struct FreeplayText;
+
+#[derive(Component)]
+pub struct DeathCauseText;
+
+#[derive(Component)]
+pub struct RunStatsText;
+
+#[derive(Component)]
+pub struct VictoryScreen;
+
+#[derive(Component)]
+pub struct BackToMenuButton;
+
+/// Volume bars shown for a moment, when any of the volumes changes.
+#[derive(Component)]
+pub struct VolumeOverlay {
+    timer: Timer,
+}
+
+#[derive(Component, Clone, Copy)]
+pub enum VolumeBarFill {
+    Music,
+    Sfx,
+}
+
+impl VolumeBarFill {
+    fn label(self) -> &'static str {
+        match self {
+            VolumeBarFill::Music => "Music",
+            VolumeBarFill::Sfx => "SFX",
+        }
+    }
+
+    fn volume(self, settings: &GameSettings) -> f32 {
+        match self {
+            VolumeBarFill::Music => settings.music_volume,
+            VolumeBarFill::Sfx => settings.sfx_volume,
+        }
+    }
+}
+
 pub struct UIPlugin;
 
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, build_hud)
-            .add_systems(Update, update_score_text)
+        app.add_systems(Startup, (build_hud, spawn_volume_overlay))
+            .add_systems(
+                Update,
+                (
+                    update_score_text,
+                    show_go_text,
+                    show_planet_name,
+                    show_close_text,
+                    fade_texts,
+                    update_combo_text,
+                    show_combo_break,
+                    animate_combo_break,
+                    update_dash_cooldown_text,
+                    update_lives_text,
+                    update_controls_hint,
+                    update_freeplay_text,
+                    show_volume_overlay,
+                    hide_volume_overlay,
+                    apply_ui_scale,
+                ),
+            )
+            .add_systems(Update, update_modifiers_text)
+            .add_systems(
+                Update,
+                (
+                    toggle_hud.run_if(in_state(AppState::Playing)),
+                    update_hud_visibility,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                interact_with_replay_button
+                    .run_if(in_state(AppState::GameOver).or_else(in_state(AppState::Victory))),
+            )
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                (show_replay_button, show_death_cause, show_run_stats),
+            )
+            .add_systems(
+                OnExit(AppState::GameOver),
+                (hide_replay_button, hide_death_cause, hide_run_stats),
+            )
+            .add_systems(
+                OnEnter(AppState::Victory),
+                (show_replay_button, show_run_stats, spawn_victory_screen),
+            )
             .add_systems(
                 Update,
-                interact_with_replay_button.run_if(in_state(AppState::GameOver)),
+                interact_with_back_to_menu_button.run_if(in_state(AppState::Victory)),
             )
-            .add_systems(OnEnter(AppState::GameOver), show_replay_button)
-            .add_systems(OnExit(AppState::GameOver), hide_replay_button);
+            .add_systems(
+                OnExit(AppState::Victory),
+                (hide_replay_button, hide_run_stats, despawn_victory_screen),
+            );
+    }
+}
+
+/// Bevy already multiplies the UI by the window scale factor, so it's divided out
+/// to make the setting the final size.
+fn ui_scale(settings: &GameSettings, window: &Window) -> UiScale {
+    let window_scale_factor = window.scale_factor();
+
+    UiScale {
+        scale: resolve_ui_scale(settings.ui_scale, window_scale_factor) as f64
+            / window_scale_factor,
     }
 }
 
-fn build_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn build_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    settings: Res<GameSettings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    if let Ok(window) = window_query.get_single() {
+        commands.insert_resource(ui_scale(&settings, window));
+    }
+
     commands
-        .spawn(NodeBundle {
-            style: MAIN_HUD_STYLE,
-            ..default()
-        })
+        .spawn((
+            NodeBundle {
+                style: MAIN_HUD_STYLE,
+                ..default()
+            },
+            MainHud,
+        ))
         .with_children(|parent| {
             // === Replay Button ===
             parent.spawn((
@@ -96,7 +432,7 @@ fn build_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
                         sections: vec![TextSection::new(
                             0.to_string(),
                             TextStyle {
-                                font: asset_server.load("fonts/Comic Sans MS.ttf"),
+                                font: game_assets.font.clone(),
                                 font_size: 48.0,
                                 color: Color::WHITE,
                             },
@@ -109,6 +445,240 @@ fn build_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
                 ScoreText {},
             ));
 
+            // === Combo text ===
+            parent.spawn((
+                TextBundle {
+                    style: COMBO_TEXT_STYLE,
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 36.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ComboText,
+            ));
+
+            // === Lives text ===
+            parent.spawn((
+                TextBundle {
+                    style: LIVES_TEXT_STYLE,
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 36.0,
+                            color: LIFE_COLOR,
+                        },
+                    ),
+                    ..default()
+                },
+                LivesText,
+            ));
+
+            // === Modifiers text ===
+            parent.spawn((
+                TextBundle {
+                    style: MODIFIERS_TEXT_STYLE,
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 20.0,
+                            color: CONTROLS_HINT_COLOR,
+                        },
+                    ),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ModifiersText,
+            ));
+
+            // === Dash cooldown text ===
+            parent.spawn((
+                TextBundle {
+                    style: DASH_TEXT_STYLE,
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 28.0,
+                            color: DASH_READY_COLOR,
+                        },
+                    ),
+                    ..default()
+                },
+                DashCooldownText,
+            ));
+
+            // === Controls hint ===
+            parent.spawn((
+                TextBundle {
+                    style: CONTROLS_HINT_STYLE,
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 20.0,
+                            color: CONTROLS_HINT_COLOR,
+                        },
+                    ),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ControlsHint,
+            ));
+
+            // === "GO!" text ===
+            parent
+                .spawn(NodeBundle {
+                    style: GO_TEXT_CONTAINER_STYLE,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                "GO!",
+                                TextStyle {
+                                    font: game_assets.font.clone(),
+                                    font_size: 96.0,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        FadingText {
+                            timer: Timer::from_seconds(GO_TEXT_DURATION, TimerMode::Once),
+                        },
+                        GoText,
+                    ));
+                });
+
+            // === Planet name ===
+            parent
+                .spawn(NodeBundle {
+                    style: PLANET_NAME_CONTAINER_STYLE,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                "",
+                                TextStyle {
+                                    font: game_assets.font.clone(),
+                                    font_size: 56.0,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        FadingText {
+                            timer: Timer::from_seconds(PLANET_NAME_DURATION, TimerMode::Once),
+                        },
+                        PlanetNameText,
+                    ));
+                });
+
+            // === Freeplay text ===
+            parent
+                .spawn(NodeBundle {
+                    style: FREEPLAY_TEXT_CONTAINER_STYLE,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                "FREEPLAY",
+                                TextStyle {
+                                    font: game_assets.font.clone(),
+                                    font_size: 28.0,
+                                    color: CONTROLS_HINT_COLOR,
+                                },
+                            ),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        FreeplayText,
+                    ));
+                });
+
+            // === Death cause ===
+            parent
+                .spawn(NodeBundle {
+                    style: DEATH_CAUSE_CONTAINER_STYLE,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                "",
+                                TextStyle {
+                                    font: game_assets.font.clone(),
+                                    font_size: 48.0,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        DeathCauseText,
+                    ));
+
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                "",
+                                TextStyle {
+                                    font: game_assets.font.clone(),
+                                    font_size: 24.0,
+                                    color: Color::WHITE,
+                                },
+                            )
+                            .with_alignment(TextAlignment::Center),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        RunStatsText,
+                    ));
+                });
+
+            // === "Close!" text ===
+            parent
+                .spawn(NodeBundle {
+                    style: CLOSE_TEXT_CONTAINER_STYLE,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                "Close!",
+                                TextStyle {
+                                    font: game_assets.font.clone(),
+                                    font_size: 64.0,
+                                    color: CLOSE_TEXT_COLOR,
+                                },
+                            ),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        FadingText {
+                            timer: Timer::from_seconds(CLOSE_TEXT_DURATION, TimerMode::Once),
+                        },
+                        CloseText,
+                    ));
+                });
+
             // === Score image ===
             parent.spawn(ImageBundle {
                 style: SCORE_IMAGE_STYLE,
@@ -118,6 +688,154 @@ fn build_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
+// Scales the fonts and the sizes of every UI node, when the setting or the display changes.
+fn apply_ui_scale(
+    mut ui_scale_resource: ResMut<UiScale>,
+    mut scale_factor_changed_event_reader: EventReader<WindowScaleFactorChanged>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<GameSettings>,
+) {
+    let is_scale_factor_changed = scale_factor_changed_event_reader.iter().next().is_some();
+
+    if !settings.is_changed() && !is_scale_factor_changed {
+        return;
+    }
+
+    if let Ok(window) = window_query.get_single() {
+        let new_ui_scale = ui_scale(&settings, window);
+
+        if ui_scale_resource.scale != new_ui_scale.scale {
+            *ui_scale_resource = new_ui_scale;
+        }
+    }
+}
+
+fn toggle_hud(keyboard_input: Res<Input<KeyCode>>, mut settings: ResMut<GameSettings>) {
+    if keyboard_input.just_pressed(HUD_TOGGLE_KEY) {
+        settings.hide_hud = !settings.hide_hud;
+    }
+}
+
+// The HUD comes back outside of runs, so the replay button shows after a run.
+fn update_hud_visibility(
+    mut hud_query: Query<&mut Visibility, With<MainHud>>,
+    settings: Res<GameSettings>,
+    app_state: Res<State<AppState>>,
+) {
+    if !settings.is_changed() && !app_state.is_changed() {
+        return;
+    }
+
+    let is_hidden = settings.hide_hud && *app_state.get() == AppState::Playing;
+
+    for mut hud_visibility in hud_query.iter_mut() {
+        *hud_visibility = if is_hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+fn spawn_volume_overlay(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: VOLUME_OVERLAY_STYLE,
+                background_color: VOLUME_OVERLAY_BACKGROUND_COLOR.into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(15),
+                ..default()
+            },
+            VolumeOverlay {
+                timer: Timer::from_seconds(VOLUME_OVERLAY_DURATION, TimerMode::Once),
+            },
+        ))
+        .with_children(|parent| {
+            for volume_bar_fill in [VolumeBarFill::Music, VolumeBarFill::Sfx] {
+                parent
+                    .spawn(NodeBundle {
+                        style: VOLUME_ROW_STYLE,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                volume_bar_fill.label(),
+                                TextStyle {
+                                    font: game_assets.font.clone(),
+                                    font_size: 24.0,
+                                    color: Color::WHITE,
+                                },
+                            )
+                            .with_style(VOLUME_LABEL_STYLE),
+                        );
+
+                        parent
+                            .spawn(NodeBundle {
+                                style: VOLUME_BAR_STYLE,
+                                background_color: VOLUME_BAR_COLOR.into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    NodeBundle {
+                                        style: VOLUME_BAR_FILL_STYLE,
+                                        background_color: VOLUME_BAR_FILL_COLOR.into(),
+                                        ..default()
+                                    },
+                                    volume_bar_fill,
+                                ));
+                            });
+                    });
+            }
+        });
+}
+
+// Shows the volume bars, when the music or the SFX volume changes.
+fn show_volume_overlay(
+    mut volume_overlay_query: Query<(&mut VolumeOverlay, &mut Visibility)>,
+    mut volume_bar_fill_query: Query<(&VolumeBarFill, &mut Style)>,
+    settings: Res<GameSettings>,
+    mut last_volumes: Local<Option<(f32, f32)>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let volumes = (settings.music_volume, settings.sfx_volume);
+    let previous_volumes = last_volumes.replace(volumes);
+
+    // The first volumes are the loaded ones, not a change.
+    if previous_volumes.is_none() || previous_volumes == Some(volumes) {
+        return;
+    }
+
+    for (volume_bar_fill, mut style) in volume_bar_fill_query.iter_mut() {
+        style.width = Val::Percent(volume_bar_fill.volume(&settings) * 100.);
+    }
+
+    if let Ok((mut volume_overlay, mut visibility)) = volume_overlay_query.get_single_mut() {
+        volume_overlay.timer.reset();
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_volume_overlay(
+    mut volume_overlay_query: Query<(&mut VolumeOverlay, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    if let Ok((mut volume_overlay, mut visibility)) = volume_overlay_query.get_single_mut() {
+        if *visibility == Visibility::Hidden {
+            return;
+        }
+
+        if volume_overlay.timer.tick(time.delta()).finished() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
 // Updates score text, if the player completed the planet.
 pub fn update_score_text(
     mut score_text_query: Query<&mut Text, With<ScoreText>>,
@@ -130,6 +848,284 @@ pub fn update_score_text(
     }
 }
 
+// Shows the "GO!" text, when the player lands and the planet starts shrinking.
+fn show_go_text(
+    mut planet_activated_event_reader: EventReader<PlanetActivatedEvent>,
+    mut go_text_query: Query<(&mut FadingText, &mut Text, &mut Visibility), With<GoText>>,
+) {
+    if planet_activated_event_reader.iter().next().is_none() {
+        return;
+    }
+
+    if let Ok((mut fading_text, mut text, mut visibility)) = go_text_query.get_single_mut() {
+        show_fading_text(&mut fading_text, &mut text, &mut visibility);
+    }
+}
+
+// Shows the name of the planet the player landed on. Story mode only,
+// infinite mode is too fast for it.
+fn show_planet_name(
+    mut planet_activated_event_reader: EventReader<PlanetActivatedEvent>,
+    mut planet_name_query: Query<
+        (&mut FadingText, &mut Text, &mut Visibility),
+        With<PlanetNameText>,
+    >,
+    game_manager: Res<GameManager>,
+) {
+    let Some(planet_activated_event) = planet_activated_event_reader.iter().last() else {
+        return;
+    };
+
+    if game_manager.infinite_mode {
+        return;
+    }
+
+    if let Ok((mut fading_text, mut text, mut visibility)) = planet_name_query.get_single_mut() {
+        text.sections[0].value = planet_activated_event.variant.name().to_string();
+        show_fading_text(&mut fading_text, &mut text, &mut visibility);
+    }
+}
+
+// Shows the "Close!" text, when an obstacle barely misses the player.
+fn show_close_text(
+    mut score_event_reader: EventReader<ScoreEvent>,
+    mut close_text_query: Query<(&mut FadingText, &mut Text, &mut Visibility), With<CloseText>>,
+) {
+    let is_near_miss = score_event_reader
+        .iter()
+        .any(|score_event| matches!(score_event.reason, ScoreReason::NearMiss));
+
+    if !is_near_miss {
+        return;
+    }
+
+    if let Ok((mut fading_text, mut text, mut visibility)) = close_text_query.get_single_mut() {
+        show_fading_text(&mut fading_text, &mut text, &mut visibility);
+    }
+}
+
+fn show_fading_text(fading_text: &mut FadingText, text: &mut Text, visibility: &mut Visibility) {
+    fading_text.timer.reset();
+    text.sections[0].style.color.set_a(1.);
+    *visibility = Visibility::Visible;
+}
+
+fn fade_texts(
+    mut fading_text_query: Query<(&mut FadingText, &mut Text, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (mut fading_text, mut text, mut visibility) in fading_text_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        fading_text.timer.tick(time.delta());
+        text.sections[0]
+            .style
+            .color
+            .set_a(fading_text.timer.percent_left());
+
+        if fading_text.timer.finished() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+// Updates combo text, if the player reached the planet fast enough.
+fn update_combo_text(
+    mut combo_text_query: Query<
+        (&mut Text, &mut Visibility),
+        (With<ComboText>, Without<ComboBreakAnimation>),
+    >,
+    combo: Res<Combo>,
+) {
+    if !combo.is_changed() {
+        return;
+    }
+
+    if let Ok((mut combo_text, mut visibility)) = combo_text_query.get_single_mut() {
+        if combo.count > 0 {
+            combo_text.sections[0].value = format!("Combo x{}", combo.count);
+            combo_text.sections[0].style.color = Color::WHITE;
+            *visibility = Visibility::Visible;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn show_combo_break(
+    mut commands: Commands,
+    mut combo_broken_event_reader: EventReader<ComboBrokenEvent>,
+    mut combo_text_query: Query<(Entity, &mut Text, &mut Visibility), With<ComboText>>,
+) {
+    for combo_broken_event in combo_broken_event_reader.iter() {
+        if let Ok((combo_text_entity, mut combo_text, mut visibility)) =
+            combo_text_query.get_single_mut()
+        {
+            combo_text.sections[0].value =
+                format!("Combo x{} lost!", combo_broken_event.lost_combo);
+            combo_text.sections[0].style.color = COMBO_BREAK_COLOR;
+            *visibility = Visibility::Visible;
+
+            commands
+                .entity(combo_text_entity)
+                .insert(ComboBreakAnimation {
+                    timer: Timer::from_seconds(COMBO_BREAK_DURATION, TimerMode::Once),
+                });
+        }
+    }
+}
+
+// Shakes the combo text and fades it out.
+fn animate_combo_break(
+    mut commands: Commands,
+    mut combo_text_query: Query<(
+        Entity,
+        &mut ComboBreakAnimation,
+        &mut Style,
+        &mut Text,
+        &mut Visibility,
+    )>,
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+) {
+    for (entity, mut animation, mut style, mut text, mut visibility) in combo_text_query.iter_mut()
+    {
+        animation.timer.tick(time.delta());
+
+        let strength = COMBO_BREAK_SHAKE_STRENGTH
+            * settings.screen_shake_intensity
+            * animation.timer.percent_left();
+        let shake = (animation.timer.elapsed_secs() * 60.).sin() * strength;
+        style.right = Val::Px(COMBO_TEXT_OFFSET.x + shake);
+        text.sections[0]
+            .style
+            .color
+            .set_a(animation.timer.percent_left());
+
+        if animation.timer.finished() {
+            style.right = Val::Px(COMBO_TEXT_OFFSET.x);
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).remove::<ComboBreakAnimation>();
+        }
+    }
+}
+
+fn update_lives_text(
+    mut lives_text_query: Query<&mut Text, With<LivesText>>,
+    game_manager: Res<GameManager>,
+) {
+    if game_manager.is_changed() {
+        if let Ok(mut lives_text) = lives_text_query.get_single_mut() {
+            lives_text.sections[0].value = LIFE_ICON.repeat(game_manager.lives as usize);
+        }
+    }
+}
+
+// Shows whether the dash is ready, or how long until it is.
+fn update_dash_cooldown_text(
+    mut dash_text_query: Query<&mut Text, With<DashCooldownText>>,
+    player_query: Query<&Player>,
+) {
+    if let Ok(mut dash_text) = dash_text_query.get_single_mut() {
+        let section = &mut dash_text.sections[0];
+
+        match player_query.get_single() {
+            Ok(player) if player.dash_cooldown.finished() => {
+                section.value = "Dash: ready".to_string();
+                section.style.color = DASH_READY_COLOR;
+            }
+            Ok(player) => {
+                section.value = format!("Dash: {:.1}s", player.dash_cooldown.remaining_secs());
+                section.style.color = DASH_COOLDOWN_COLOR;
+            }
+            Err(_) => section.value.clear(),
+        }
+    }
+}
+
+fn controls_hint_text(settings: &GameSettings) -> String {
+    let key_bindings = &settings.key_bindings;
+    let fast_fall_action = if settings.invert_fast_fall {
+        "Rise"
+    } else {
+        "Fall"
+    };
+
+    let jump_action = if settings.auto_jump {
+        "Hold to stay"
+    } else {
+        "Jump"
+    };
+
+    format!(
+        "{:?}: {}  {:?}: {}  {:?}: Dash",
+        key_bindings.jump, jump_action, key_bindings.fast_fall, fast_fall_action, key_bindings.dash
+    )
+}
+
+fn update_controls_hint(
+    mut controls_hint_query: Query<(&mut Text, &mut Visibility), With<ControlsHint>>,
+    settings: Res<GameSettings>,
+    app_state: Res<State<AppState>>,
+) {
+    if !settings.is_changed() && !app_state.is_changed() {
+        return;
+    }
+
+    if let Ok((mut text, mut visibility)) = controls_hint_query.get_single_mut() {
+        text.sections[0].value = controls_hint_text(&settings);
+        *visibility = if settings.show_controls_hint && *app_state.get() == AppState::Playing {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_freeplay_text(
+    mut freeplay_text_query: Query<&mut Visibility, With<FreeplayText>>,
+    settings: Res<GameSettings>,
+    app_state: Res<State<AppState>>,
+) {
+    if !settings.is_changed() && !app_state.is_changed() {
+        return;
+    }
+
+    if let Ok(mut visibility) = freeplay_text_query.get_single_mut() {
+        *visibility = if settings.freeplay && *app_state.get() == AppState::Playing {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_modifiers_text(
+    mut modifiers_text_query: Query<(&mut Text, &mut Visibility), With<ModifiersText>>,
+    run_modifiers: Res<RunModifiers>,
+    app_state: Res<State<AppState>>,
+) {
+    if !run_modifiers.is_changed() && !app_state.is_changed() {
+        return;
+    }
+
+    if let Ok((mut text, mut visibility)) = modifiers_text_query.get_single_mut() {
+        text.sections[0].value = run_modifiers
+            .0
+            .iter()
+            .map(|modifier| modifier.name())
+            .collect::<Vec<_>>()
+            .join("\n");
+        *visibility = if !run_modifiers.0.is_empty() && *app_state.get() == AppState::Playing {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
 fn show_replay_button(mut replay_button_query: Query<&mut Visibility, With<ReplayButton>>) {
     if let Ok(mut replay_button_visibility) = replay_button_query.get_single_mut() {
         *replay_button_visibility = Visibility::Visible;
@@ -142,6 +1138,138 @@ fn hide_replay_button(mut replay_button_query: Query<&mut Visibility, With<Repla
     }
 }
 
+fn show_death_cause(
+    mut death_cause_text_query: Query<(&mut Text, &mut Visibility), With<DeathCauseText>>,
+    death_cause: Option<Res<DeathCause>>,
+) {
+    let Some(death_cause) = death_cause else {
+        return;
+    };
+
+    if let Ok((mut text, mut visibility)) = death_cause_text_query.get_single_mut() {
+        text.sections[0].value = death_cause.message().to_string();
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_death_cause(
+    mut commands: Commands,
+    mut death_cause_text_query: Query<&mut Visibility, With<DeathCauseText>>,
+) {
+    if let Ok(mut visibility) = death_cause_text_query.get_single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+
+    commands.remove_resource::<DeathCause>();
+}
+
+// The seed goes last, so it's easy to find and share.
+fn show_run_stats(
+    mut run_stats_text_query: Query<(&mut Text, &mut Visibility), With<RunStatsText>>,
+    run_stats: Res<RunStats>,
+    game_rng: Res<GameRng>,
+) {
+    if let Ok((mut text, mut visibility)) = run_stats_text_query.get_single_mut() {
+        text.sections[0].value = format!("{}\nSeed: {}", run_stats.to_text(), game_rng.seed);
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_run_stats(mut run_stats_text_query: Query<&mut Visibility, With<RunStatsText>>) {
+    if let Ok(mut visibility) = run_stats_text_query.get_single_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn spawn_victory_screen(
+    mut commands: Commands,
+    game_manager: Res<GameManager>,
+    game_assets: Res<GameAssets>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: VICTORY_SCREEN_STYLE,
+                ..default()
+            },
+            VictoryScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Victory!",
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 96.0,
+                    color: VICTORY_TITLE_COLOR,
+                },
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "All {} planets cleared with {} points",
+                    game_manager.planets_cleared, game_manager.score
+                ),
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: SETTING_BUTTON_STYLE,
+                        background_color: NORMAL_SETTING_BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    BackToMenuButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Menu",
+                        TextStyle {
+                            font: game_assets.font.clone(),
+                            font_size: 28.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+        });
+}
+
+fn interact_with_back_to_menu_button(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<BackToMenuButton>),
+    >,
+    mut app_state_next_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_SETTING_BUTTON_COLOR.into();
+                app_state_next_state.set(AppState::Menu);
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_SETTING_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_SETTING_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+fn despawn_victory_screen(
+    mut commands: Commands,
+    victory_screen_query: Query<Entity, With<VictoryScreen>>,
+) {
+    for victory_screen_entity in victory_screen_query.iter() {
+        commands.entity(victory_screen_entity).despawn_recursive();
+    }
+}
+
 fn interact_with_replay_button(
     mut button_query: Query<
         (&Interaction, &mut BackgroundColor),
@@ -0,0 +1,175 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{pause::PauseState, AppState};
+
+pub const WARP_STREAK_SIZE: Vec2 = Vec2::new(4., 160.);
+pub const WARP_STREAK_SPEED: f32 = 2500.;
+pub const WARP_STREAK_LIFETIME: f32 = 0.5;
+pub const WARP_STREAK_INTERVAL: f32 = 1. / 60.;
+pub const WARP_STREAK_COLOR: Color = Color::rgba(1., 1., 1., 0.6);
+// Highest opacity of the white overlay, in the middle of the transition.
+pub const WARP_OVERLAY_MAX_ALPHA: f32 = 0.25;
+
+pub const WARP_OVERLAY_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.position_type = PositionType::Absolute;
+    style.width = Val::Percent(100.);
+    style.height = Val::Percent(100.);
+    style
+};
+
+/// Present while the camera moves to the next planet with the warp effect setting on.
+#[derive(Resource)]
+pub struct Transitioning(pub Timer);
+
+#[derive(Component)]
+pub struct WarpStreak {
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+pub struct WarpOverlay;
+
+pub struct WarpPlugin;
+
+impl Plugin for WarpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_warp_overlay)
+            .add_systems(
+                Update,
+                (
+                    tick_transition,
+                    spawn_warp_streaks,
+                    move_warp_streaks,
+                    update_warp_overlay,
+                )
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(OnExit(AppState::Playing), end_transition);
+    }
+}
+
+fn spawn_warp_overlay(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: WARP_OVERLAY_STYLE,
+            background_color: Color::NONE.into(),
+            ..default()
+        },
+        WarpOverlay,
+    ));
+}
+
+fn tick_transition(
+    mut commands: Commands,
+    transitioning: Option<ResMut<Transitioning>>,
+    time: Res<Time>,
+) {
+    if let Some(mut transitioning) = transitioning {
+        if transitioning.0.tick(time.delta()).finished() {
+            commands.remove_resource::<Transitioning>();
+        }
+    }
+}
+
+// Spawns streaks below the view, rushing up past the descending camera.
+fn spawn_warp_streaks(
+    mut commands: Commands,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    transitioning: Option<Res<Transitioning>>,
+    time: Res<Time>,
+    mut spawn_timer: Local<Option<Timer>>,
+) {
+    if transitioning.is_none() {
+        return;
+    }
+
+    let Ok((camera_transform, camera_projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    let spawn_timer = spawn_timer
+        .get_or_insert_with(|| Timer::from_seconds(WARP_STREAK_INTERVAL, TimerMode::Repeating));
+    spawn_timer.tick(time.delta());
+
+    let view_area = camera_projection.area;
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..spawn_timer.times_finished_this_tick() {
+        let streak_position = Vec3::new(
+            camera_transform.translation.x + rng.gen_range(view_area.min.x..view_area.max.x),
+            camera_transform.translation.y + view_area.min.y,
+            5.,
+        );
+
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(streak_position),
+                sprite: Sprite {
+                    custom_size: Some(WARP_STREAK_SIZE),
+                    color: WARP_STREAK_COLOR,
+                    ..default()
+                },
+                ..default()
+            },
+            WarpStreak {
+                lifetime: Timer::from_seconds(WARP_STREAK_LIFETIME, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn move_warp_streaks(
+    mut commands: Commands,
+    mut streak_query: Query<(Entity, &mut Transform, &mut Sprite, &mut WarpStreak)>,
+    time: Res<Time>,
+) {
+    for (streak_entity, mut streak_transform, mut streak_sprite, mut warp_streak) in
+        streak_query.iter_mut()
+    {
+        streak_transform.translation.y += WARP_STREAK_SPEED * time.delta_seconds();
+
+        warp_streak.lifetime.tick(time.delta());
+        streak_sprite
+            .color
+            .set_a(WARP_STREAK_COLOR.a() * warp_streak.lifetime.percent_left());
+
+        if warp_streak.lifetime.finished() {
+            commands.entity(streak_entity).despawn_recursive();
+        }
+    }
+}
+
+// Flashes the screen white, strongest in the middle of the transition.
+fn update_warp_overlay(
+    mut overlay_query: Query<&mut BackgroundColor, With<WarpOverlay>>,
+    transitioning: Option<Res<Transitioning>>,
+) {
+    let alpha = transitioning.map_or(0., |transitioning| {
+        (transitioning.0.percent() * PI).sin() * WARP_OVERLAY_MAX_ALPHA
+    });
+
+    if let Ok(mut overlay_color) = overlay_query.get_single_mut() {
+        overlay_color.0 = Color::WHITE.with_a(alpha);
+    }
+}
+
+fn end_transition(
+    mut commands: Commands,
+    mut overlay_query: Query<&mut BackgroundColor, With<WarpOverlay>>,
+    streak_query: Query<Entity, With<WarpStreak>>,
+) {
+    commands.remove_resource::<Transitioning>();
+
+    for streak_entity in streak_query.iter() {
+        commands.entity(streak_entity).despawn_recursive();
+    }
+
+    if let Ok(mut overlay_color) = overlay_query.get_single_mut() {
+        overlay_color.0 = Color::NONE;
+    }
+}